@@ -0,0 +1,108 @@
+//! # bool-to-bitflags-runtime
+//!
+//! The runtime companion of [`bool_to_bitflags`](https://docs.rs/bool_to_bitflags), which cannot export traits as
+//! it is a proc-macro crate.
+//!
+//! With the `runtime` argument of `bool_to_bitflags`, such as `#[bool_to_bitflags(runtime)]`, the compacted struct
+//! implements [`BoolPacked`], so generic code can work with any of them.
+//!
+//! ```
+//! use bool_to_bitflags_runtime::BoolPacked;
+//!
+//! fn count_set<T: BoolPacked>(packed: &T) -> usize {
+//!     (0..T::FIELD_COUNT)
+//!         .filter_map(|index| packed.get_by_index(index))
+//!         .filter(|value| value.is_true())
+//!         .count()
+//! }
+//! ```
+//!
+//! ## MSRV
+//! The Minimum Supported Rust Version of this crate is 1.65.
+//!
+//! It is considered a breaking change to raise this requirement.
+#![no_std]
+#![warn(clippy::pedantic, rust_2018_idioms, missing_docs)]
+
+use core::fmt;
+
+/// The value of a packed field, depending on if the field is a `bool` or an `Option<bool>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PackedValue {
+    /// The value of a `bool` field.
+    Bool(bool),
+    /// The value of an `Option<bool>` field.
+    OptBool(Option<bool>),
+}
+
+impl PackedValue {
+    /// Returns if the value is `true` or `Some(true)`.
+    #[must_use]
+    pub fn is_true(self) -> bool {
+        matches!(self, Self::Bool(true) | Self::OptBool(Some(true)))
+    }
+}
+
+impl From<bool> for PackedValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<Option<bool>> for PackedValue {
+    fn from(value: Option<bool>) -> Self {
+        Self::OptBool(value)
+    }
+}
+
+/// The error returned by [`BoolPacked::set_by_index`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SetByIndexError {
+    /// The index is not less than [`BoolPacked::FIELD_COUNT`].
+    OutOfBounds,
+    /// The value does not match the type of the field, such as a `bool` for an `Option<bool>` field.
+    WrongType,
+}
+
+impl fmt::Display for SetByIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::OutOfBounds => "the field index is out of bounds",
+            Self::WrongType => "the value does not match the type of the field",
+        })
+    }
+}
+
+/// A struct with its `bool` and `Option<bool>` fields packed by `#[bool_to_bitflags]`.
+///
+/// Packed fields are indexed in declaration order, skipping any fields which are configured out with `cfg`.
+pub trait BoolPacked: Sized {
+    /// The generated flags type of the fields without a group, or of the first group if every field has one.
+    ///
+    /// The flags of any other group are not covered, but can still be read and written with [`Self::get_by_index`]
+    /// and [`Self::set_by_index`].
+    type Flags;
+    /// The unpacked form of the struct, `{StructName}GeneratedOriginal`.
+    type Original;
+
+    /// The number of packed fields.
+    const FIELD_COUNT: usize;
+
+    /// Returns the names of the packed fields, in index order.
+    fn field_names() -> &'static [&'static str];
+
+    /// Returns the value of the packed field at `index`, or `None` if it is out of bounds.
+    fn get_by_index(&self, index: usize) -> Option<PackedValue>;
+
+    /// Sets the value of the packed field at `index`.
+    ///
+    /// # Errors
+    /// Errors if `index` is out of bounds, or `value` does not match the type of the field.
+    fn set_by_index(&mut self, index: usize, value: PackedValue) -> Result<(), SetByIndexError>;
+
+    /// Converts into the unpacked form of the struct.
+    fn into_original(self) -> Self::Original;
+
+    /// Converts from the unpacked form of the struct.
+    fn from_original(original: Self::Original) -> Self;
+}
@@ -1,12 +1,63 @@
 #![allow(clippy::manual_unwrap_or_default)] // Emitted inside darling's code
 use darling::ast::NestedMeta;
+use darling::util::{PathList, SpannedValue};
 use darling::FromMeta;
 use proc_macro2::TokenStream;
+use syn::Attribute;
 
 use crate::error::Error;
 
+/// Where the generated flags field is placed in the struct.
+#[derive(darling::FromMeta, Clone, Copy, Default)]
+#[darling(rename_all = "snake_case")]
+pub enum FlagsPosition {
+    First,
+    #[default]
+    Last,
+    FirstBool,
+}
+
+/// A list of attributes without the `#[...]`, such as `getter_attrs(track_caller, wasm_bindgen(getter))`.
+#[derive(Default)]
+pub struct AttrList(pub Vec<syn::Meta>);
+
+impl FromMeta for AttrList {
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        let mut errors = darling::Error::accumulator();
+        let metas = items
+            .iter()
+            .filter_map(|item| match item {
+                NestedMeta::Meta(meta) => Some(meta.clone()),
+                NestedMeta::Lit(lit) => {
+                    errors.push(darling::Error::unexpected_lit_type(lit));
+                    None
+                }
+            })
+            .collect();
+
+        errors.finish_with(Self(metas))
+    }
+}
+
+/// The path to `bool_to_bitflags_runtime`, such as `runtime = my_crate::bool_to_bitflags_runtime` or just `runtime`.
+pub struct RuntimePath(pub syn::Path);
+
+impl FromMeta for RuntimePath {
+    fn from_word() -> darling::Result<Self> {
+        Ok(Self(syn::parse_quote!(::bool_to_bitflags_runtime)))
+    }
+
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        syn::Path::from_expr(expr).map(Self)
+    }
+
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        syn::Path::from_value(value).map(Self)
+    }
+}
+
 /// Match documentation to crate level docs!!
-#[derive(darling::FromMeta)]
+#[derive(darling::FromMeta, Default)]
 #[allow(clippy::struct_excessive_bools)] // I would love to use bool_to_bitflags here... but
 pub struct Args {
     pub getter_prefix: Option<String>,
@@ -19,6 +70,29 @@ pub struct Args {
     pub document_setters: bool,
     #[darling(default)]
     pub owning_setters: bool,
+    pub flags_derive: Option<PathList>,
+    #[darling(default)]
+    pub cell: bool,
+    pub assert_size: Option<SpannedValue<usize>>,
+    #[darling(default)]
+    pub assert_smaller: SpannedValue<bool>,
+    #[darling(default)]
+    pub allow_no_savings: bool,
+    pub flags_position: Option<FlagsPosition>,
+    pub storage: Option<syn::Path>,
+    #[darling(default)]
+    pub getter_attrs: AttrList,
+    #[darling(default)]
+    pub setter_attrs: AttrList,
+    #[darling(default)]
+    pub allow_unused: bool,
+    #[darling(default)]
+    pub doc_hidden: bool,
+    #[darling(default)]
+    pub compare_original: SpannedValue<bool>,
+    #[darling(default)]
+    pub debug: SpannedValue<bool>,
+    pub runtime: Option<RuntimePath>,
 }
 
 impl Args {
@@ -26,3 +100,35 @@ impl Args {
         Self::from_list(&NestedMeta::parse_meta_list(args)?).map_err(Error::Darling)
     }
 }
+
+/// Arguments passed via `#[bitflags(...)]` on an individual field.
+#[derive(darling::FromMeta, Default)]
+pub struct FieldArgs {
+    pub default: Option<syn::Expr>,
+    #[darling(default)]
+    pub inverted: bool,
+    pub flag: Option<syn::LitStr>,
+    pub tag_flag: Option<syn::LitStr>,
+    pub group: Option<syn::LitStr>,
+}
+
+impl FieldArgs {
+    pub fn is_field_args(attr: &Attribute) -> bool {
+        attr.path().is_ident("bitflags")
+    }
+
+    pub fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut nested_metas = Vec::new();
+        for attr in attrs.iter().filter(|attr| Self::is_field_args(attr)) {
+            let syn::Meta::List(meta_list) = &attr.meta else {
+                return Err(Error::Darling(
+                    darling::Error::unsupported_format("non-list").with_span(attr),
+                ));
+            };
+
+            nested_metas.extend(NestedMeta::parse_meta_list(meta_list.tokens.clone())?);
+        }
+
+        Self::from_list(&nested_metas).map_err(Error::Darling)
+    }
+}
@@ -0,0 +1,93 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{punctuated::Punctuated, Attribute, Meta, Token};
+
+/// The predicates of the `cfg_attr`s which an attribute is nested in, all of which must hold.
+#[derive(Clone, Default)]
+pub struct CfgCondition(Vec<Meta>);
+
+impl CfgCondition {
+    /// If the attribute is not inside of any `cfg_attr`.
+    pub fn is_always(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Generates a `cfg` predicate which holds if this condition does.
+    pub fn predicate(&self) -> TokenStream {
+        let predicates = &self.0;
+        quote!(all(#(#predicates),*))
+    }
+
+    /// Generates an attribute applying `meta` under this condition.
+    pub fn wrap(&self, meta: &impl ToTokens) -> Attribute {
+        if self.is_always() {
+            syn::parse_quote!(#[#meta])
+        } else {
+            let predicate = self.predicate();
+            syn::parse_quote!(#[cfg_attr(#predicate, #meta)])
+        }
+    }
+}
+
+/// Generates `#[cfg(...)]` which holds if any of `conditions` do, or nothing if one always holds.
+fn generate_any_cfg(conditions: &[CfgCondition]) -> TokenStream {
+    if conditions.iter().any(CfgCondition::is_always) {
+        return TokenStream::new();
+    }
+
+    let predicates = conditions.iter().map(CfgCondition::predicate);
+    quote!(#[cfg(any(#(#predicates),*))])
+}
+
+/// Generates the items from `generate` if derived under any of `conditions`, nesting them in an anonymous const
+/// so the `cfg` applies to every item.
+pub fn generate_derived(
+    conditions: &[CfgCondition],
+    generate: impl FnOnce() -> TokenStream,
+) -> Option<TokenStream> {
+    if conditions.is_empty() {
+        return None;
+    }
+
+    let items = generate();
+    let cfg = generate_any_cfg(conditions);
+    Some(if cfg.is_empty() {
+        items
+    } else {
+        quote!(#cfg const _: () = { #items };)
+    })
+}
+
+fn expand_meta(condition: &CfgCondition, meta: Meta, expanded: &mut Vec<(CfgCondition, Meta)>) {
+    if let Meta::List(meta_list) = &meta {
+        if meta_list.path.is_ident("cfg_attr") {
+            let parser = Punctuated::<Meta, Token![,]>::parse_terminated;
+            // Malformed `cfg_attr`s are kept as is, for the compiler to report.
+            if let Ok(metas) = meta_list.parse_args_with(parser) {
+                let mut metas = metas.into_iter();
+                if let Some(predicate) = metas.next() {
+                    let mut condition = condition.clone();
+                    condition.0.push(predicate);
+
+                    for meta in metas {
+                        expand_meta(&condition, meta, expanded);
+                    }
+
+                    return;
+                }
+            }
+        }
+    }
+
+    expanded.push((condition.clone(), meta));
+}
+
+/// Expands every `cfg_attr` in `attrs`, including nested ones, into the attributes they apply and their conditions.
+pub fn expand_cfg_attrs(attrs: &[Attribute]) -> Vec<(CfgCondition, Meta)> {
+    let mut expanded = Vec::new();
+    for attr in attrs.iter().filter(|a| a.style == syn::AttrStyle::Outer) {
+        expand_meta(&CfgCondition::default(), attr.meta.clone(), &mut expanded);
+    }
+
+    expanded
+}
@@ -0,0 +1,242 @@
+use proc_macro2::{Ident, Span};
+use quote::format_ident;
+use syn::{ext::IdentExt, Fields};
+
+use crate::{
+    args::Args,
+    error::Error,
+    impl_bits::bits_method_names,
+    impl_get_set::args_to_names,
+    r#impl::{extract_cfgs, BoolField, FlagGroup},
+};
+
+/// An identifier which the macro generates, or which already exists in the struct.
+struct Name {
+    ident: String,
+    /// Where to report a collision, or `None` if this name is not written by the user.
+    span: Option<Span>,
+    /// A description of where the name comes from, for the error message.
+    source: String,
+    /// If the name only exists under `#[cfg(...)]`, so may not actually collide.
+    guarded: bool,
+}
+
+impl Name {
+    fn new(name: &Ident, span: Option<Span>, source: String, guarded: bool) -> Self {
+        Self {
+            ident: name.unraw().to_string(),
+            span,
+            source,
+            guarded,
+        }
+    }
+}
+
+/// Pushes an error at every user written name in `names` which collides with another name.
+fn find_collisions(names: &[Name], errors: &mut Vec<syn::Error>) {
+    for (index, name) in names.iter().enumerate() {
+        let Some(span) = name.span else {
+            continue;
+        };
+
+        let colliding: Vec<&str> = names
+            .iter()
+            .enumerate()
+            .filter(|(other_index, other)| *other_index != index && other.ident == name.ident)
+            // Names guarded by `cfg`s could be mutually exclusive, which cannot be known here.
+            .filter(|(_, other)| !(name.guarded && other.guarded))
+            .map(|(_, other)| other.source.as_str())
+            .collect();
+
+        if !colliding.is_empty() {
+            let err_msg = format!(
+                "bool_to_bitflags: `{}`, {}, collides with {}.",
+                name.ident,
+                name.source,
+                colliding.join(", ")
+            );
+
+            errors.push(syn::Error::new(span, err_msg));
+        }
+    }
+}
+
+fn collect_method_names(groups: &[FlagGroup], bool_fields: &[BoolField], args: &Args) -> Vec<Name> {
+    let mut names = vec![
+        Name::new(
+            &format_ident!("bools"),
+            None,
+            String::from("the `bools` method"),
+            false,
+        ),
+        Name::new(
+            &format_ident!("set_bools"),
+            None,
+            String::from("the `set_bools` method"),
+            false,
+        ),
+    ];
+
+    for group in groups {
+        let bits_kinds = [
+            "bits getter",
+            "bits setter",
+            "truncating bits setter",
+            "unchecked bits setter",
+        ];
+        let bits_names = bits_method_names(group.name.as_ref());
+        for (method_name, method_kind) in bits_names.iter().zip(bits_kinds) {
+            let source = match &group.name {
+                Some(group_name) => format!("the {method_kind} of group `{group_name}`"),
+                None => format!("the `{method_name}` method"),
+            };
+
+            let span = group.name.as_ref().map(Ident::span);
+            names.push(Name::new(method_name, span, source, false));
+        }
+
+        let Some(group_name) = &group.name else {
+            continue;
+        };
+
+        let span = Some(group_name.span());
+        names.push(Name::new(
+            &format_ident!("{}_flags", group_name),
+            span,
+            format!("the flags getter of group `{group_name}`"),
+            false,
+        ));
+        names.push(Name::new(
+            &format_ident!("clear_{}", group_name),
+            span,
+            format!("the clear method of group `{group_name}`"),
+            false,
+        ));
+    }
+
+    for field in bool_fields {
+        let field_name = &field.field_ident;
+        let guarded = !extract_cfgs(&field.attrs).is_empty();
+        let span = Some(field_name.span());
+
+        let (getter_name, setter_name) = args_to_names(args, field_name);
+        let methods = [
+            (format_ident!("{}_ref", getter_name), "reference getter"),
+            (format_ident!("{}_mut", getter_name), "mutable guard getter"),
+            (getter_name, "getter"),
+            (setter_name, "setter"),
+        ];
+
+        for (method_name, method_kind) in methods {
+            let source = format!("the {method_kind} of `{field_name}`");
+            names.push(Name::new(&method_name, span, source, guarded));
+        }
+    }
+
+    names
+}
+
+fn collect_flag_names(bool_fields: &[&BoolField]) -> Vec<Name> {
+    let mut names = Vec::new();
+    for field in bool_fields {
+        let field_name = &field.field_ident;
+        let guarded = !extract_cfgs(&field.attrs).is_empty();
+
+        let flag_span = field
+            .args
+            .flag
+            .as_ref()
+            .map_or(field_name.span(), syn::LitStr::span);
+        let source = format!("the flag of `{field_name}`");
+        names.push(Name::new(
+            &field.flag_ident,
+            Some(flag_span),
+            source,
+            guarded,
+        ));
+
+        if let Some(tag_bit_flag_ident) = field.tag_bit_flag_ident() {
+            let tag_span = field.args.tag_flag.as_ref();
+            let tag_span = tag_span.map_or(field_name.span(), syn::LitStr::span);
+            let source = format!("the tag flag of `{field_name}`");
+            names.push(Name::new(
+                tag_bit_flag_ident,
+                Some(tag_span),
+                source,
+                guarded,
+            ));
+        }
+    }
+
+    names
+}
+
+fn collect_field_names(
+    user_fields: &Fields,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+) -> Vec<Name> {
+    let mut names = Vec::new();
+    for field in user_fields {
+        let Some(field_name) = &field.ident else {
+            continue;
+        };
+
+        // Packed fields are removed from the struct, so cannot collide.
+        if bool_fields.iter().any(|f| &f.field_ident == field_name) {
+            continue;
+        }
+
+        let guarded = !extract_cfgs(&field.attrs).is_empty();
+        let source = format!("the field `{field_name}`");
+        names.push(Name::new(
+            field_name,
+            Some(field_name.span()),
+            source,
+            guarded,
+        ));
+    }
+
+    for group in groups {
+        let source = match &group.name {
+            Some(group_name) => format!("the generated flags field of group `{group_name}`"),
+            None => String::from("the generated flags field"),
+        };
+
+        names.push(Name::new(&group.flag_field.ident, None, source, false));
+    }
+
+    names
+}
+
+/// Errors if any generated method, flag constant, or field name is not unique, pointing at every collision.
+pub fn check_collisions(
+    user_fields: &Fields,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+    args: &Args,
+) -> Result<(), Error> {
+    let mut errors = Vec::new();
+
+    let method_names = collect_method_names(groups, bool_fields, args);
+    find_collisions(&method_names, &mut errors);
+
+    for (index, _) in groups.iter().enumerate() {
+        let group_fields: Vec<_> = bool_fields.iter().filter(|f| f.group == index).collect();
+        find_collisions(&collect_flag_names(&group_fields), &mut errors);
+    }
+
+    let field_names = collect_field_names(user_fields, groups, bool_fields);
+    find_collisions(&field_names, &mut errors);
+
+    let mut errors = errors.into_iter();
+    let Some(mut combined) = errors.next() else {
+        return Ok(());
+    };
+
+    for error in errors {
+        combined.combine(error);
+    }
+
+    Err(Error::Syn(combined))
+}
@@ -0,0 +1,68 @@
+use std::{borrow::Cow, path::PathBuf};
+
+use proc_macro2::{Ident, Span, TokenStream};
+
+use crate::error::Error;
+
+/// Pretty-prints `expansion` if the `pretty` feature is enabled, and it parses as a file.
+#[cfg(feature = "pretty")]
+fn format_expansion(expansion: &TokenStream) -> String {
+    match syn::parse2::<syn::File>(expansion.clone()) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(_) => expansion.to_string(),
+    }
+}
+
+#[cfg(not(feature = "pretty"))]
+fn format_expansion(expansion: &TokenStream) -> String {
+    expansion.to_string()
+}
+
+/// The name of the file the expansion of `struct_name` is written to.
+///
+/// The line of the struct is included to keep same-named structs in different modules apart, but is only known
+/// with the `debug` feature, on Rust 1.88 and above.
+#[cfg(feature = "debug")]
+fn expansion_file_name(struct_name: &Ident) -> String {
+    match struct_name.span().start().line {
+        0 => format!("{struct_name}.rs"),
+        line => format!("{struct_name}.{line}.rs"),
+    }
+}
+
+#[cfg(not(feature = "debug"))]
+fn expansion_file_name(struct_name: &Ident) -> String {
+    format!("{struct_name}.rs")
+}
+
+/// Writes the `expansion` to `{OUT_DIR}/bool_to_bitflags/{StructName}.rs`, for the `debug` argument.
+pub fn write_expansion(
+    struct_name: &Ident,
+    expansion: &TokenStream,
+    span: Span,
+) -> Result<(), Error> {
+    let Some(out_dir) = std::env::var_os("OUT_DIR") else {
+        return Err(Error::Custom(
+            span,
+            Cow::Borrowed(
+                "bool_to_bitflags: `debug` writes to `OUT_DIR`, which is only set for crates with a build script!",
+            ),
+        ));
+    };
+
+    let dir = PathBuf::from(out_dir).join("bool_to_bitflags");
+    let path = dir.join(expansion_file_name(struct_name));
+    let write_result = std::fs::create_dir_all(&dir)
+        .and_then(|()| std::fs::write(&path, format_expansion(expansion)));
+
+    if let Err(err) = write_result {
+        let err_msg = format!(
+            "bool_to_bitflags: Failed to write the expansion to `{}`: {err}",
+            path.display()
+        );
+
+        return Err(Error::Custom(span, Cow::Owned(err_msg)));
+    }
+
+    Ok(())
+}
@@ -2,9 +2,13 @@ use std::borrow::Cow;
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
-use syn::{punctuated::Punctuated, spanned::Spanned, ItemStruct, Path, Token};
+use syn::{punctuated::Punctuated, spanned::Spanned, Attribute, ItemStruct, Meta, Path, Token};
 
-use crate::error::Error;
+use crate::{
+    args::Args,
+    cfg_attr::{expand_cfg_attrs, CfgCondition},
+    error::{Accumulator, Error},
+};
 
 fn new_basic_segment(ident: &'static str) -> syn::PathSegment {
     syn::PathSegment {
@@ -13,12 +17,113 @@ fn new_basic_segment(ident: &'static str) -> syn::PathSegment {
     }
 }
 
+/// The conditions each custom impl is derived under, which is not derived if empty.
+#[derive(Default)]
+struct HijackState {
+    serde_from: Vec<Attribute>,
+    serde_into: Vec<Attribute>,
+    typesize: Vec<CfgCondition>,
+    default: Vec<CfgCondition>,
+    partial_eq: Vec<CfgCondition>,
+}
+
+/// Derives which are known to work on the generated flags type, used unless `flags_derive` is passed, with the
+/// `core` module they are defined in.
+const SAFE_FLAGS_DERIVES: &[(&str, &str)] = &[
+    ("Clone", "clone"),
+    ("Copy", "marker"),
+    ("Debug", "fmt"),
+    ("Default", "default"),
+    ("PartialEq", "cmp"),
+    ("Eq", "cmp"),
+    ("PartialOrd", "cmp"),
+    ("Ord", "cmp"),
+    ("Hash", "hash"),
+];
+
+/// Returns the name of the derive, if it is `Derive` or `{std,core}::path::to::Derive`.
+///
+/// This avoids matching third party derive macros with the same name as a standard library derive.
+fn std_derive_name(path: &Path) -> Option<&Ident> {
+    let mut segments = path.segments.iter();
+    let last_segment = segments.next_back()?;
+
+    let is_std_path = match segments.next() {
+        Some(first_segment) => first_segment.ident == "std" || first_segment.ident == "core",
+        None => path.leading_colon.is_none(),
+    };
+
+    (is_std_path && last_segment.arguments.is_none()).then_some(&last_segment.ident)
+}
+
+/// Returns the fully qualified path of a derive which is known to work on the generated flags type.
+pub fn safe_flags_derive_path(path: &Path) -> Option<Path> {
+    let name = std_derive_name(path)?;
+    let (_, module) = SAFE_FLAGS_DERIVES
+        .iter()
+        .find(|(derive, _)| name == derive)?;
+
+    let module = Ident::new(module, Span::call_site());
+    Some(syn::parse_quote!(::core::#module::#name))
+}
+
+fn is_safe_flags_derive(path: &Path) -> bool {
+    safe_flags_derive_path(path).is_some()
+}
+
+fn is_default_derive(path: &Path) -> bool {
+    std_derive_name(path).map_or(false, |name| name == "Default")
+}
+
+fn is_partial_eq_derive(path: &Path) -> bool {
+    std_derive_name(path).map_or(false, |name| name == "PartialEq")
+}
+
+/// Errors on standard library derives which `Cell` does not implement, so cannot be derived in `cell` mode.
+fn check_cell_derives(derive_paths: &Punctuated<Path, Token![,]>, errors: &mut Accumulator) {
+    for path in derive_paths {
+        if std_derive_name(path).map_or(false, |name| name == "Hash") {
+            errors.push(Error::Custom(
+                path.span(),
+                Cow::Borrowed("bool_to_bitflags: `Hash` cannot be derived with `cell`, as `Cell` does not implement `Hash`."),
+            ));
+        }
+    }
+}
+
+fn parse_derive_paths(meta: &Meta) -> syn::Result<Punctuated<Path, Token![,]>> {
+    meta.require_list()?
+        .parse_args_with(Punctuated::parse_terminated)
+}
+
+/// Removes `Default` from the derives of the original struct, as it is implemented by `impl_default`.
+pub fn strip_default_derive(original_struct: &mut ItemStruct) {
+    let mut attrs = Vec::with_capacity(original_struct.attrs.len());
+    for (condition, meta) in expand_cfg_attrs(&original_struct.attrs) {
+        if !meta.path().is_ident("derive") {
+            attrs.push(condition.wrap(&meta));
+            continue;
+        }
+
+        let Ok(derive_paths) = parse_derive_paths(&meta) else {
+            // Already reported by `hijack_derives`.
+            continue;
+        };
+
+        let derive_paths = derive_paths.iter().filter(|p| !is_default_derive(p));
+        attrs.push(condition.wrap(&quote!(derive(#(#derive_paths),*))));
+    }
+
+    original_struct.attrs = attrs;
+}
+
 fn set_custom_impls(
     original_name: &Ident,
-    serde_from: &mut Option<TokenStream>,
-    serde_into: &mut Option<TokenStream>,
+    state: &mut HijackState,
+    condition: &CfgCondition,
     derive_macros: Punctuated<Path, Token![,]>,
-) -> Result<TokenStream, Error> {
+    errors: &mut Accumulator,
+) -> Vec<Path> {
     let original_name = original_name.to_string();
     let serde_segment = new_basic_segment("serde");
     let serialize_segment = new_basic_segment("Serialize");
@@ -28,7 +133,7 @@ fn set_custom_impls(
     let typesize_derive_segment = new_basic_segment("derive");
     let typesize_last_segment = new_basic_segment("TypeSize");
 
-    let mut filtered_derives = Vec::new();
+    let mut compacted_derives = Vec::new();
     for path in derive_macros {
         let mut path_iter = path.segments.iter();
         let Some(first_segment) = path_iter.next() else {
@@ -37,70 +142,137 @@ fn set_custom_impls(
 
         if first_segment != &serde_segment && first_segment != &typesize_first_segment {
             if first_segment == &serialize_segment || first_segment == &deserialize_segment {
-                return Err(Error::Custom(
+                errors.push(Error::Custom(
                     path.span(),
                     Cow::Borrowed("bool_to_bitflags: Please be specific with this derive path."),
                 ));
+                continue;
+            }
+
+            if is_default_derive(&path) {
+                // The compacted struct gets a handwritten impl, see `impl_default`.
+                state.default.push(condition.clone());
+                continue;
+            }
+
+            if is_partial_eq_derive(&path) {
+                state.partial_eq.push(condition.clone());
             }
 
-            filtered_derives.push(path);
+            compacted_derives.push(path);
             continue;
         }
 
         let Some(next_segment) = path_iter.next() else {
-            filtered_derives.push(path);
+            compacted_derives.push(path);
             continue;
         };
 
         if next_segment == &serialize_segment {
-            *serde_into = Some(quote!(#[serde(into = #original_name)]));
+            let serde_attr = quote!(serde(into = #original_name));
+            state.serde_into.push(condition.wrap(&serde_attr));
         } else if next_segment == &deserialize_segment {
-            *serde_from = Some(quote!(#[serde(from = #original_name)]));
-        } else if !(next_segment == &typesize_derive_segment
-            && path_iter.next() == Some(&typesize_last_segment))
+            let serde_attr = quote!(serde(from = #original_name));
+            state.serde_from.push(condition.wrap(&serde_attr));
+        } else if cfg!(feature = "typesize")
+            && next_segment == &typesize_derive_segment
+            && path_iter.next() == Some(&typesize_last_segment)
         {
-            filtered_derives.push(path);
+            // The compacted struct gets a handwritten impl, see `impl_typesize`.
+            state.typesize.push(condition.clone());
+            continue;
         }
+
+        compacted_derives.push(path);
     }
 
-    Ok(quote!(#[derive(#(#filtered_derives),*)]))
+    compacted_derives
 }
 
+/// The derives of the compacted struct, with the `cfg_attr` conditions each custom impl is derived under.
 pub struct HijackOutput {
     pub compacted_struct_attrs: Vec<TokenStream>,
-    pub flags_derives: Vec<TokenStream>,
+    pub flags_derives: Vec<(CfgCondition, Path)>,
+    pub derives_typesize: Vec<CfgCondition>,
+    pub derives_default: Vec<CfgCondition>,
+    pub derives_partial_eq: Vec<CfgCondition>,
 }
 
 pub fn hijack_derives(
     compacted_struct: &mut ItemStruct,
     original_name: &Ident,
-) -> Result<HijackOutput, Error> {
-    let mut serde_from = None;
-    let mut serde_into = None;
+    args: &Args,
+    errors: &mut Accumulator,
+) -> HijackOutput {
+    let mut state = HijackState::default();
     let mut flags_derives = Vec::new();
-    for attr in &compacted_struct.attrs {
-        if attr.path().is_ident("derive") {
-            let parser = Punctuated::<Path, Token![,]>::parse_terminated;
-            flags_derives.push(set_custom_impls(
-                original_name,
-                &mut serde_from,
-                &mut serde_into,
-                attr.parse_args_with(parser)?,
-            )?);
+    let mut compacted_attrs = Vec::new();
+    for (condition, meta) in expand_cfg_attrs(&compacted_struct.attrs) {
+        if meta.path().is_ident("serde") {
+            continue;
         }
+
+        if !meta.path().is_ident("derive") {
+            compacted_attrs.push(condition.wrap(&meta).to_token_stream());
+            continue;
+        }
+
+        let Some(derive_paths) = errors.handle(parse_derive_paths(&meta).map_err(Error::Syn))
+        else {
+            continue;
+        };
+
+        if args.cell {
+            check_cell_derives(&derive_paths, errors);
+        }
+
+        flags_derives.extend(
+            derive_paths
+                .iter()
+                .filter(|p| is_safe_flags_derive(p))
+                .map(|p| (condition.clone(), p.clone())),
+        );
+
+        let compacted_derive_paths =
+            set_custom_impls(original_name, &mut state, &condition, derive_paths, errors);
+        if !compacted_derive_paths.is_empty() {
+            let derive_attr = quote!(derive(#(#compacted_derive_paths),*));
+            compacted_attrs.push(condition.wrap(&derive_attr).to_token_stream());
+        }
+    }
+
+    compacted_struct.attrs.clear();
+    compacted_attrs.extend(state.serde_from.iter().map(ToTokens::to_token_stream));
+    compacted_attrs.extend(state.serde_into.iter().map(ToTokens::to_token_stream));
+
+    if let Some(flags_derive_arg) = &args.flags_derive {
+        flags_derives = flags_derive_arg
+            .iter()
+            .map(|p| (CfgCondition::default(), p.clone()))
+            .collect();
     }
 
-    let compacted_attrs = compacted_struct
-        .attrs
-        .drain(..)
-        .filter(|a| !a.path().is_ident("serde"))
-        .map(|a| a.to_token_stream())
-        .chain(serde_from)
-        .chain(serde_into)
-        .collect();
+    if args.cell {
+        // `Cell::get` requires the flags to be `Copy`, even when the conditional derives are off.
+        let required_derives: [(&str, Path); 2] = [
+            ("Clone", syn::parse_quote!(::core::clone::Clone)),
+            ("Copy", syn::parse_quote!(::core::marker::Copy)),
+        ];
 
-    Ok(HijackOutput {
+        for (required, path) in required_derives {
+            if !flags_derives.iter().any(|(condition, p)| {
+                condition.is_always() && std_derive_name(p).map_or(false, |name| name == required)
+            }) {
+                flags_derives.push((CfgCondition::default(), path));
+            }
+        }
+    }
+
+    HijackOutput {
         compacted_struct_attrs: compacted_attrs,
         flags_derives,
-    })
+        derives_typesize: state.typesize,
+        derives_default: state.default,
+        derives_partial_eq: state.partial_eq,
+    }
 }
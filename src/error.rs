@@ -6,6 +6,7 @@ pub enum Error {
     Syn(syn::Error),
     Darling(darling::Error),
     Custom(Span, Cow<'static, str>),
+    Multiple(Vec<Error>),
 }
 
 impl Error {
@@ -14,6 +15,7 @@ impl Error {
             Self::Syn(err) => err.to_compile_error(),
             Self::Darling(err) => err.write_errors(),
             Self::Custom(span, err) => syn::Error::new(span, err).to_compile_error(),
+            Self::Multiple(errors) => errors.into_iter().map(Self::into_compile_error).collect(),
         }
     }
 }
@@ -23,3 +25,32 @@ impl From<syn::Error> for Error {
         Self::Syn(value)
     }
 }
+
+/// Collects errors from independent checks, so they can all be reported in one compile.
+#[derive(Default)]
+pub struct Accumulator(Vec<Error>);
+
+impl Accumulator {
+    pub fn push(&mut self, error: Error) {
+        self.0.push(error);
+    }
+
+    /// Returns the value of `result`, or stores the error and returns `None`.
+    pub fn handle<T>(&mut self, result: Result<T, Error>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(error);
+                None
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<(), Error> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Multiple(self.0))
+        }
+    }
+}
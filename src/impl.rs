@@ -2,15 +2,27 @@ use std::borrow::Cow;
 
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
-use syn::{Attribute, Field, Fields, Ident, Token};
+use syn::{
+    ext::IdentExt, punctuated::Punctuated, spanned::Spanned, Attribute, Field, Fields, Ident, Token,
+};
 use to_arraystring::ToArrayString;
 
 use crate::{
-    args::Args,
-    derive_hijack::{hijack_derives, HijackOutput},
-    error::Error,
+    args::{Args, FieldArgs, FlagsPosition},
+    cfg_attr::{expand_cfg_attrs, generate_derived, CfgCondition},
+    collisions::check_collisions,
+    debug::write_expansion,
+    derive_hijack::{hijack_derives, safe_flags_derive_path, strip_default_derive, HijackOutput},
+    error::{Accumulator, Error},
+    impl_bits::impl_bits,
+    impl_bools::impl_bools,
+    impl_default::{check_unused_defaults, impl_default},
     impl_from_into::{impl_from, impl_into},
     impl_get_set::generate_getters_setters,
+    impl_partial_eq::{check_compare_original, impl_partial_eq},
+    impl_runtime::impl_runtime,
+    impl_size::{generate_size_checks, impl_bytes_saved},
+    impl_typesize::impl_typesize,
     strip_spans::strip_spans,
 };
 
@@ -19,6 +31,9 @@ pub struct BoolFieldInner {
     pub flag_ident: Ident,
     pub attrs: Vec<syn::Attribute>,
     pub vis: syn::Visibility,
+    pub args: FieldArgs,
+    /// The index of the [`FlagGroup`] this field is packed into.
+    pub group: usize,
 }
 
 pub enum BoolField {
@@ -29,27 +44,90 @@ pub enum BoolField {
     },
 }
 
+/// Errors for attributes on a packed field which cannot be honoured on its accessors.
+fn check_field_attrs(attrs: &[Attribute], errors: &mut Accumulator) {
+    for (condition, meta) in expand_cfg_attrs(attrs) {
+        if !condition.is_always() && meta.path().is_ident("bitflags") {
+            errors.push(Error::Custom(
+                meta.span(),
+                Cow::Borrowed("bool_to_bitflags: `bitflags` cannot be applied via `cfg_attr`, as the layout of the flags is decided before `cfg` is evaluated."),
+            ));
+        } else if meta.path().is_ident("expect") {
+            errors.push(Error::Custom(
+                meta.span(),
+                Cow::Borrowed("bool_to_bitflags: `expect` is not supported on packed fields, as it is forwarded to every accessor. Use `allow` instead."),
+            ));
+        }
+    }
+}
+
 impl BoolField {
-    fn from_field(field: &Field) -> Self {
+    /// Invalid arguments are stored in `errors` and replaced with the defaults, to find any further errors.
+    fn from_field(field: &Field, errors: &mut Accumulator) -> Self {
         let field_ident = field.ident.clone().unwrap();
+        check_field_attrs(&field.attrs, errors);
+
+        let args = errors
+            .handle(FieldArgs::parse(&field.attrs))
+            .unwrap_or_default();
+
+        let flag_ident = args
+            .flag
+            .as_ref()
+            .and_then(|flag| errors.handle(flag.parse().map_err(Error::Syn)));
+        // Spanned to the field, so errors from every use of a flag missing from `storage` are reported once there.
+        let flag_ident = flag_ident.unwrap_or_else(|| {
+            Ident::new(
+                &field_ident.unraw().to_string().to_uppercase(),
+                field_ident.span(),
+            )
+        });
+
         BoolField::Normal(BoolFieldInner {
-            flag_ident: Ident::new(&field_ident.to_string().to_uppercase(), Span::call_site()),
-            attrs: field.attrs.clone(),
+            flag_ident,
+            attrs: field
+                .attrs
+                .iter()
+                .filter(|attr| !FieldArgs::is_field_args(attr))
+                .cloned()
+                .collect(),
             vis: field.vis.clone(),
             field_ident,
+            args,
+            group: 0,
         })
     }
 
-    fn from_opt_bool_field(field: &Field) -> Self {
-        match Self::from_field(field) {
+    fn from_opt_bool_field(field: &Field, errors: &mut Accumulator) -> Self {
+        match Self::from_field(field, errors) {
             BoolField::Opt { .. } => unreachable!(),
-            BoolField::Normal(bool_bit) => BoolField::Opt {
-                tag_bit_flag_ident: format_ident!("{}_OPT_TAG", bool_bit.flag_ident),
-                bool_bit,
-            },
+            BoolField::Normal(bool_bit) => {
+                let tag_flag = bool_bit.args.tag_flag.as_ref();
+                let tag_bit_flag_ident =
+                    tag_flag.and_then(|tag| errors.handle(tag.parse().map_err(Error::Syn)));
+
+                BoolField::Opt {
+                    tag_bit_flag_ident: tag_bit_flag_ident
+                        .unwrap_or_else(|| format_ident!("{}_OPT_TAG", bool_bit.flag_ident)),
+                    bool_bit,
+                }
+            }
+        }
+    }
+
+    /// The type of the field before packing, either `bool` or `Option<bool>`.
+    pub fn value_ty(&self) -> TokenStream {
+        match self {
+            BoolField::Normal(_) => quote!(bool),
+            BoolField::Opt { .. } => quote!(::core::option::Option<bool>),
         }
     }
 
+    /// The group this field is packed into.
+    pub fn group<'a>(&self, groups: &'a [FlagGroup]) -> &'a FlagGroup {
+        &groups[self.group]
+    }
+
     pub fn tag_bit_flag_ident(&self) -> Option<&Ident> {
         match self {
             BoolField::Normal(_) => None,
@@ -70,6 +148,163 @@ impl std::ops::Deref for BoolField {
     }
 }
 
+impl std::ops::DerefMut for BoolField {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            BoolField::Normal(inner) => inner,
+            BoolField::Opt { bool_bit, .. } => bool_bit,
+        }
+    }
+}
+
+/// The generated field which stores the packed flags.
+pub struct FlagField {
+    pub ident: Ident,
+    /// If the flags are wrapped in a `Cell`, set by the `cell` argument.
+    pub cell: bool,
+}
+
+impl FlagField {
+    /// Generates an expression to copy the flags out of `receiver`.
+    pub fn read(&self, receiver: &TokenStream) -> TokenStream {
+        let ident = &self.ident;
+        if self.cell {
+            quote!(#receiver.#ident.get())
+        } else {
+            quote!(#receiver.#ident)
+        }
+    }
+
+    /// Generates an expression to mutably borrow the flags out of a mutably borrowed `receiver`.
+    pub fn get_mut(&self, receiver: &TokenStream) -> TokenStream {
+        let ident = &self.ident;
+        if self.cell {
+            quote!(#receiver.#ident.get_mut())
+        } else {
+            quote!((&mut #receiver.#ident))
+        }
+    }
+
+    /// Generates statements to modify the flags in `receiver` via `modify`, which takes a `&mut` place.
+    ///
+    /// If `shared` is true, `receiver` is only borrowed immutably, so must be in `cell` mode.
+    pub fn update(
+        &self,
+        receiver: &TokenStream,
+        shared: bool,
+        modify: impl FnOnce(&TokenStream) -> TokenStream,
+    ) -> TokenStream {
+        let ident = &self.ident;
+        if shared {
+            let flags = local_ident("flags");
+            let body = modify(&quote!(#flags));
+            quote!(
+                let mut #flags = #receiver.#ident.get();
+                #body
+                #receiver.#ident.set(#flags);
+            )
+        } else {
+            modify(&self.get_mut(receiver))
+        }
+    }
+
+    /// Generates a statement to replace the flags in `receiver` with `flags`.
+    pub fn store(&self, receiver: &TokenStream, flags: &TokenStream) -> TokenStream {
+        let ident = &self.ident;
+        if self.cell {
+            quote!(#receiver.#ident.set(#flags);)
+        } else {
+            quote!(#receiver.#ident = #flags;)
+        }
+    }
+
+    /// Generates an expression to wrap `flags` into the type of this field.
+    pub fn wrap(&self, flags: &TokenStream) -> TokenStream {
+        if self.cell {
+            quote!(::core::cell::Cell::new(#flags))
+        } else {
+            flags.clone()
+        }
+    }
+
+    fn ty(&self, flags_path: syn::Path) -> syn::Type {
+        let flags_ty = syn::Type::Path(syn::TypePath {
+            qself: None,
+            path: flags_path,
+        });
+
+        if self.cell {
+            syn::parse_quote!(::core::cell::Cell<#flags_ty>)
+        } else {
+            flags_ty
+        }
+    }
+}
+
+impl quote::ToTokens for FlagField {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+    }
+}
+
+/// A set of bools packed into the same flags field, split up by `#[bitflags(group = "...")]`.
+pub struct FlagGroup {
+    /// The name of the group, without any `r#`, or `None` for bools without a `group` argument.
+    pub name: Option<Ident>,
+    pub flag_field: FlagField,
+    /// The name of the generated flags type, unused if `storage` is passed.
+    pub flags_name: Ident,
+    /// The path to the flags type, either `flags_name` or the `storage` argument.
+    pub flags_path: syn::Path,
+}
+
+impl FlagGroup {
+    fn new(struct_name: &Ident, name: Option<Ident>, args: &Args) -> Self {
+        // Hidden flags type should not have the span of the struct's name.
+        let (field_ident, flags_name) = match &name {
+            Some(name) => (
+                format_ident!("__generated_{}_flags", name, span = Span::call_site()),
+                format_ident!(
+                    "{}{}GeneratedFlags",
+                    struct_name,
+                    to_upper_camel_case(&name.to_string()),
+                    span = Span::call_site()
+                ),
+            ),
+            None => (
+                Ident::new("__generated_flags", Span::call_site()),
+                format_ident!("{}GeneratedFlags", struct_name, span = Span::call_site()),
+            ),
+        };
+
+        let flags_path = match (&name, &args.storage) {
+            (None, Some(storage)) => storage.clone(),
+            _ => path_from_ident(flags_name.clone()),
+        };
+
+        Self {
+            name,
+            flag_field: FlagField {
+                ident: field_ident,
+                cell: args.cell,
+            },
+            flags_name,
+            flags_path,
+        }
+    }
+}
+
+/// Converts a `snake_case` group name into `UpperCamelCase`, for the name of the flags type.
+fn to_upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().into_iter().flat_map(char::to_uppercase);
+            first.chain(chars)
+        })
+        .collect()
+}
+
 fn path_from_ident(ident: Ident) -> syn::Path {
     syn::Path {
         leading_colon: None,
@@ -82,6 +317,14 @@ fn path_from_ident(ident: Ident) -> syn::Path {
     }
 }
 
+/// Creates the identifier of a local variable or parameter in the expansion, which cannot be used by user code.
+///
+/// Items are still resolved at the call site with `Span::mixed_site`, so the name is also prefixed to avoid being
+/// interpreted as a pattern matching a constant of the same name.
+pub fn local_ident(name: &str) -> Ident {
+    Ident::new(&format!("__{name}"), Span::mixed_site())
+}
+
 pub fn generate_pub_crate() -> syn::Visibility {
     syn::Visibility::Restricted(syn::VisRestricted {
         pub_token: <Token![pub]>::default(),
@@ -91,37 +334,120 @@ pub fn generate_pub_crate() -> syn::Visibility {
     })
 }
 
+/// Ranks how widely visible `vis` is, with `pub(in path)` visibilities all ranked the same.
+fn visibility_rank(vis: &syn::Visibility) -> u8 {
+    match vis {
+        syn::Visibility::Public(_) => 3,
+        syn::Visibility::Restricted(restricted) if restricted.path.is_ident("crate") => 2,
+        syn::Visibility::Restricted(restricted) if restricted.path.is_ident("self") => 0,
+        syn::Visibility::Restricted(_) => 1,
+        syn::Visibility::Inherited => 0,
+    }
+}
+
+/// Finds the most restrictive of `visibilities`, for items which expose every packed field they touch.
+///
+/// Two different `pub(in path)` visibilities cannot be compared here, so fall back to private.
+pub fn most_restrictive_vis<'a>(
+    visibilities: impl IntoIterator<Item = &'a syn::Visibility>,
+) -> syn::Visibility {
+    let mut most_restrictive: Option<syn::Visibility> = None;
+    for vis in visibilities {
+        most_restrictive = Some(match most_restrictive {
+            None => vis.clone(),
+            Some(current) => match visibility_rank(vis).cmp(&visibility_rank(&current)) {
+                std::cmp::Ordering::Less => vis.clone(),
+                std::cmp::Ordering::Greater => current,
+                std::cmp::Ordering::Equal if &current == vis => current,
+                std::cmp::Ordering::Equal => syn::Visibility::Inherited,
+            },
+        });
+    }
+
+    most_restrictive.unwrap_or(syn::Visibility::Inherited)
+}
+
+/// Finds the least restrictive of `visibilities`, for items which are exposed by every item they are used in.
+///
+/// Two different `pub(in path)` visibilities cannot be compared here, so fall back to `pub(crate)`.
+pub fn least_restrictive_vis<'a>(
+    visibilities: impl IntoIterator<Item = &'a syn::Visibility>,
+) -> syn::Visibility {
+    let mut least_restrictive: Option<syn::Visibility> = None;
+    for vis in visibilities {
+        least_restrictive = Some(match least_restrictive {
+            None => vis.clone(),
+            Some(current) => match visibility_rank(vis).cmp(&visibility_rank(&current)) {
+                std::cmp::Ordering::Greater => vis.clone(),
+                std::cmp::Ordering::Less => current,
+                std::cmp::Ordering::Equal if &current == vis => current,
+                std::cmp::Ordering::Equal => generate_pub_crate(),
+            },
+        });
+    }
+
+    least_restrictive.unwrap_or(syn::Visibility::Inherited)
+}
+
+/// Creates an identifier from `name`, as a raw identifier if it is a keyword, such as a getter for `r#type`.
+pub fn ident_or_raw(name: &str, span: Span) -> Ident {
+    if syn::parse_str::<Ident>(name).is_ok() {
+        Ident::new(name, span)
+    } else {
+        Ident::new_raw(name, span)
+    }
+}
+
 pub fn ty_from_ident(ident: syn::Ident) -> syn::Type {
     let path = path_from_ident(ident);
     syn::Type::Path(syn::TypePath { qself: None, path })
 }
 
-pub fn extract_cfgs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> + Clone {
-    attrs.iter().filter(|attr| {
-        if attr.style != syn::AttrStyle::Outer {
-            return false;
-        }
+/// Adds `bound` to every type parameter, the same as the bounds added by std derives.
+pub fn add_trait_bounds(generics: &syn::Generics, bound: &syn::TypeParamBound) -> syn::Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(bound.clone());
+    }
 
-        let syn::Meta::List(meta_list) = &attr.meta else {
-            return false;
-        };
+    generics
+}
 
-        let Some(ident) = meta_list.path.get_ident() else {
-            return false;
-        };
+/// Extracts the `cfg`s of a field, converting those inside of `cfg_attr` into an equivalent `cfg`.
+pub fn extract_cfgs(attrs: &[Attribute]) -> Vec<Attribute> {
+    expand_cfg_attrs(attrs)
+        .into_iter()
+        .filter_map(|(condition, meta)| {
+            let syn::Meta::List(meta_list) = meta else {
+                return None;
+            };
 
-        ident == "cfg"
-    })
+            if !meta_list.path.is_ident("cfg") {
+                return None;
+            }
+
+            if condition.is_always() {
+                return Some(syn::parse_quote!(#[#meta_list]));
+            }
+
+            // `cfg_attr(condition, cfg(predicate))` only removes the field if `condition` holds and `predicate` does not.
+            let condition = condition.predicate();
+            let predicate = meta_list.tokens;
+            Some(syn::parse_quote!(#[cfg(any(not(#condition), #predicate))]))
+        })
+        .collect()
 }
 
-fn generate_flag_field(flags_ident: Ident, field_ident: Ident) -> Field {
+fn generate_flag_field(flags_path: syn::Path, flag_field: &FlagField) -> Field {
     Field {
-        attrs: Vec::new(),
-        ident: Some(field_ident),
+        attrs: vec![syn::parse_quote!(
+            #[doc = "The packed bools, which should be accessed via the generated getters and setters."]
+        )],
+        ident: Some(flag_field.ident.clone()),
         vis: generate_pub_crate(),
         mutability: syn::FieldMutability::None,
         colon_token: Some(<Token![:]>::default()),
-        ty: ty_from_ident(flags_ident),
+        ty: flag_field.ty(flags_path),
     }
 }
 
@@ -134,35 +460,40 @@ fn generate_generic(ty: syn::Type) -> syn::PathArguments {
     })
 }
 
-fn is_bool_field(bool_fields: &mut Vec<BoolField>) -> impl FnMut(&Field) -> bool + '_ {
+enum BoolFieldKind {
+    Normal,
+    Opt,
+}
+
+fn bool_field_kind() -> impl Fn(&Field) -> Option<BoolFieldKind> {
     let bool_ident = Ident::new("bool", Span::call_site());
     let opt_ident = Ident::new("Option", Span::call_site());
     let bool_generic = generate_generic(ty_from_ident(bool_ident.clone()));
 
     move |field| {
-        if let syn::Type::Path(ty) = &field.ty {
-            let segments = &ty.path.segments;
-            let first_seg = segments.first().expect("field type path has one segment");
-
-            if first_seg.ident == opt_ident && first_seg.arguments == bool_generic {
-                bool_fields.push(BoolField::from_opt_bool_field(field));
-            } else if first_seg.ident == bool_ident {
-                bool_fields.push(BoolField::from_field(field));
-            } else {
-                return true;
-            }
+        let syn::Type::Path(ty) = &field.ty else {
+            return None;
+        };
 
-            return false;
-        }
+        let segments = &ty.path.segments;
+        let first_seg = segments.first().expect("field type path has one segment");
 
-        true
+        if first_seg.ident == opt_ident && first_seg.arguments == bool_generic {
+            Some(BoolFieldKind::Opt)
+        } else if first_seg.ident == bool_ident {
+            Some(BoolFieldKind::Normal)
+        } else {
+            None
+        }
     }
 }
 
 fn extract_bool_fields(
-    flag_field: Field,
+    struct_name: &Ident,
+    args: &Args,
     fields: Fields,
-) -> Result<(Fields, Vec<BoolField>), Error> {
+    errors: &mut Accumulator,
+) -> Result<(Fields, Vec<FlagGroup>, Vec<BoolField>), Error> {
     let Fields::Named(mut fields) = fields else {
         return Err(Error::Custom(
             Span::call_site(),
@@ -170,42 +501,203 @@ fn extract_bool_fields(
         ));
     };
 
+    let bool_field_kind = bool_field_kind();
+    let mut groups: Vec<FlagGroup> = Vec::new();
     let mut bool_fields = Vec::new();
-    fields.named = fields
-        .named
-        .into_iter()
-        .filter(is_bool_field(&mut bool_fields))
-        .chain(std::iter::once(flag_field))
-        .collect();
+    let mut kept_fields = Punctuated::new();
+    // The index of the first bool of each group, within `kept_fields`.
+    let mut first_bool_indexes = Vec::new();
+    for field in fields.named {
+        let mut bool_field = match bool_field_kind(&field) {
+            Some(BoolFieldKind::Normal) => BoolField::from_field(&field, errors),
+            Some(BoolFieldKind::Opt) => BoolField::from_opt_bool_field(&field, errors),
+            None => {
+                if let Some(attr) = field.attrs.iter().find(|a| FieldArgs::is_field_args(a)) {
+                    // Report invalid arguments before the field type.
+                    if errors.handle(FieldArgs::parse(&field.attrs)).is_some() {
+                        errors.push(Error::Custom(
+                            attr.span(),
+                            Cow::Borrowed("bool_to_bitflags: `#[bitflags(...)]` is only supported on bool fields!"),
+                        ));
+                    }
+                }
+
+                kept_fields.push(field);
+                continue;
+            }
+        };
+
+        let group_name = bool_field.args.group.as_ref().and_then(|group| {
+            let group_name = group.parse_with(Ident::parse_any).map_err(Error::Syn);
+            errors.handle(group_name).map(|name| name.unraw())
+        });
+
+        let group_index = if let Some(index) = groups.iter().position(|g| g.name == group_name) {
+            index
+        } else {
+            groups.push(FlagGroup::new(struct_name, group_name, args));
+            first_bool_indexes.push(kept_fields.len());
+            groups.len() - 1
+        };
+
+        bool_field.group = group_index;
+        bool_fields.push(bool_field);
+    }
+
+    if groups.is_empty() {
+        groups.push(FlagGroup::new(struct_name, None, args));
+        first_bool_indexes.push(kept_fields.len());
+    }
+
+    // Inserted in reverse, so groups sharing an index stay in order and earlier indexes stay valid.
+    let kept_len = kept_fields.len();
+    for (group, first_bool_index) in groups.iter().zip(first_bool_indexes).rev() {
+        let flag_field_index = match args.flags_position.unwrap_or_default() {
+            FlagsPosition::First => 0,
+            FlagsPosition::Last => kept_len,
+            FlagsPosition::FirstBool => first_bool_index,
+        };
+
+        let flag_field = generate_flag_field(group.flags_path.clone(), &group.flag_field);
+        kept_fields.insert(flag_field_index, flag_field);
+    }
 
-    Ok((Fields::Named(fields), bool_fields))
+    fields.named = kept_fields;
+    Ok((Fields::Named(fields), groups, bool_fields))
 }
 
-fn get_flag_size(bool_count: usize) -> Result<syn::Type, Error> {
-    let ty_name = match bool_count {
+/// Errors if the struct has a layout-affecting `repr`, unless `flags_position` is explicitly passed.
+fn check_repr(struct_item: &syn::ItemStruct, args: &Args) -> Result<(), Error> {
+    if args.flags_position.is_some() {
+        return Ok(());
+    }
+
+    for (_, meta) in expand_cfg_attrs(&struct_item.attrs) {
+        if !meta.path().is_ident("repr") {
+            continue;
+        }
+
+        let parser = Punctuated::<syn::Meta, Token![,]>::parse_terminated;
+        for repr in meta.require_list()?.parse_args_with(parser)? {
+            let Some(repr_ident) = repr.path().get_ident() else {
+                continue;
+            };
+
+            if repr_ident == "C" || repr_ident == "packed" || repr_ident == "transparent" {
+                let err_msg = format!(
+                    "bool_to_bitflags: Packing changes the layout of `repr({repr_ident})` structs, pass `flags_position` to opt in."
+                );
+
+                return Err(Error::Custom(repr.span(), Cow::Owned(err_msg)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The most bits which fit in a single bitflags type, a `u128`.
+const MAX_FLAG_BITS: usize = 128;
+
+/// The number of bits a field uses, as `Option<bool>` fields need an extra tag bit.
+fn bit_count(field: &BoolField) -> usize {
+    match field {
+        BoolField::Normal(_) => 1,
+        BoolField::Opt { .. } => 2,
+    }
+}
+
+/// Generates an error pointing at the first field which does not fit into the flags type of `group`.
+/// Describes how many bits `count` fields of type `ty` use, such as "1 `bool` field uses 1 bit".
+fn describe_field_bits(count: usize, ty: &str, bits_per_field: usize) -> String {
+    let bits = count * bits_per_field;
+    let (field_noun, use_verb) = if count == 1 {
+        ("field", "uses")
+    } else {
+        ("fields", "use")
+    };
+    let bit_noun = if bits == 1 { "bit" } else { "bits" };
+
+    format!("{count} `{ty}` {field_noun} {use_verb} {bits} {bit_noun}")
+}
+
+fn generate_overflow_error(group: &FlagGroup, bool_fields: &[&BoolField]) -> Error {
+    let mut used_bits = 0;
+    let overflowing_field = bool_fields
+        .iter()
+        .find(|field| {
+            used_bits += bit_count(field);
+            used_bits > MAX_FLAG_BITS
+        })
+        .expect("overflow error should only be generated if fields do not fit");
+
+    let opt_count = bool_fields
+        .iter()
+        .filter(|f| matches!(f, BoolField::Opt { .. }))
+        .count();
+    let bool_count = bool_fields.len() - opt_count;
+    let total_bits = bool_count + (opt_count * 2);
+
+    let group_desc = match &group.name {
+        Some(name) => format!("group `{name}` needs"),
+        None => String::from("bools without a group need"),
+    };
+
+    let err_msg = format!(
+        "bool_to_bitflags: `{}` does not fit, as the {group_desc} {total_bits} bits but a bitflags type holds at most {MAX_FLAG_BITS}. \
+        {} and {}, consider moving some into another `group`.",
+        overflowing_field.field_ident,
+        describe_field_bits(bool_count, "bool", 1),
+        describe_field_bits(opt_count, "Option<bool>", 2),
+    );
+
+    Error::Custom(overflowing_field.field_ident.span(), Cow::Owned(err_msg))
+}
+
+fn get_flag_size(group: &FlagGroup, bool_fields: &[&BoolField]) -> Result<syn::Type, Error> {
+    let total_bits: usize = bool_fields.iter().map(|f| bit_count(f)).sum();
+    let ty_name = match total_bits {
         0..=8 => "u8",
         9..=16 => "u16",
         17..=32 => "u32",
         33..=64 => "u64",
-        65..=128 => "u128",
-        _ => {
-            let err_msg = format!(
-                "bool_to_bitflags: Cannot fit {bool_count} bool fields into single bitflags type!"
-            );
-
-            return Err(Error::Custom(Span::call_site(), Cow::Owned(err_msg)));
-        }
+        65..=MAX_FLAG_BITS => "u128",
+        _ => return Err(generate_overflow_error(group, bool_fields)),
     };
 
     Ok(ty_from_ident(Ident::new(ty_name, Span::call_site())))
 }
 
+/// Generates checks that every flag exists in a user provided `storage` type, pointing at the offending field.
+fn generate_storage_checks(storage: &syn::Path, bool_fields: &[&BoolField]) -> TokenStream {
+    let checks = bool_fields.iter().map(|field| {
+        let cfgs = extract_cfgs(&field.attrs);
+        let flag_idents = std::iter::once(&field.flag_ident).chain(field.tag_bit_flag_ident());
+
+        quote!(#(#cfgs)* const _: () = { #(let _ = #storage::#flag_idents;)* };)
+    });
+
+    quote!(#(#checks)*)
+}
+
+/// Generates the `bitflags!` invocation of the flags type of `group`, which is placed two modules down by
+/// [`generate_flags_module`].
 fn generate_bitflags_type(
-    flags_name: &Ident,
+    struct_name: &Ident,
+    flags_vis: &syn::Visibility,
+    group: &FlagGroup,
     flags_size: &syn::Type,
-    bool_fields: &[BoolField],
-    flags_derives: &[TokenStream],
+    bool_fields: &[&BoolField],
+    flags_attrs: &[Attribute],
 ) -> TokenStream {
+    let flags_name = &group.flags_name;
+    let flags_docs = match &group.name {
+        Some(group_name) => format!(
+            "The packed bools of the `{group_name}` group of [`{struct_name}`](super::super::{struct_name})."
+        ),
+        None => format!("The packed bools of [`{struct_name}`](super::super::{struct_name})."),
+    };
+
     let opt_bools = bool_fields.iter().filter_map(|f| f.tag_bit_flag_ident());
     let flag_values = (0..(bool_fields.len() + opt_bools.clone().count()))
         .map(|i| (1_u128 << i).to_arraystring())
@@ -216,9 +708,9 @@ fn generate_bitflags_type(
         let field_tag_cfgs = bool_fields.iter().map(|f| {
             let field_cfgs = extract_cfgs(&f.attrs);
             let tag_cfgs = if matches!(f, BoolField::Opt { .. }) {
-                extract_cfgs(&f.attrs)
+                field_cfgs.clone()
             } else {
-                extract_cfgs(&[])
+                Vec::new()
             };
 
             (quote!(#(#field_cfgs)*), quote!(#(#tag_cfgs)*))
@@ -228,86 +720,339 @@ fn generate_bitflags_type(
         field_cfgs.into_iter().chain(tag_cfgs)
     };
 
+    let flag_docs = {
+        let field_names = bool_fields.iter().map(|f| f.field_ident.unraw());
+        let field_docs = field_names.map(|name| format!("The bit storing `{name}`."));
+        let tag_docs = bool_fields
+            .iter()
+            .filter(|f| matches!(f, BoolField::Opt { .. }))
+            .map(|f| format!("The bit storing if `{}` is `Some`.", f.field_ident.unraw()));
+
+        field_docs.chain(tag_docs)
+    };
+
+    quote!(super::__bitflags::bitflags! {
+        #[doc = #flags_docs]
+        #(#flags_attrs)*
+        #flags_vis struct #flags_name: #flags_size {
+            #(#[doc = #flag_docs] #flag_cfgs const #flag_names = #flag_values;)*
+        }
+    })
+}
+
+/// Replaces the docs of the original struct, which would duplicate doc tests, and documents undocumented fields.
+///
+/// With the `doc_hidden` argument, the original struct is also hidden from the docs.
+fn generate_original_docs(original_struct: &mut syn::ItemStruct, struct_name: &Ident, args: &Args) {
+    let struct_docs = format!(
+        "The unpacked form of [`{struct_name}`], which can be converted to and from with `From`."
+    );
+
+    original_struct
+        .attrs
+        .retain(|attr| !attr.path().is_ident("doc"));
+    original_struct
+        .attrs
+        .insert(0, syn::parse_quote!(#[doc = #struct_docs]));
+    if args.doc_hidden {
+        original_struct
+            .attrs
+            .insert(1, syn::parse_quote!(#[doc(hidden)]));
+    }
+
+    for field in &mut original_struct.fields {
+        if field.attrs.iter().any(|attr| attr.path().is_ident("doc")) {
+            continue;
+        }
+
+        if let Some(field_name) = &field.ident {
+            let field_docs = format!("The {} of [`{struct_name}`].", field_name.unraw());
+            field
+                .attrs
+                .insert(0, syn::parse_quote!(#[doc = #field_docs]));
+        }
+    }
+}
+
+/// Generates `#[allow(dead_code)]` for generated items, if the `allow_unused` argument is passed.
+pub fn generate_allow_unused(args: &Args) -> Option<TokenStream> {
+    args.allow_unused.then(|| quote!(#[allow(dead_code)]))
+}
+
+/// Adjusts a `self::` or `super::` path to be resolved from a module one level down.
+fn path_from_child_module(path: &syn::Path) -> syn::Path {
+    let Some(first_segment) = path
+        .segments
+        .first()
+        .filter(|_| path.leading_colon.is_none())
+    else {
+        return path.clone();
+    };
+
+    let mut path = path.clone();
+    if first_segment.ident == "self" {
+        path.segments[0].ident = Ident::new("super", first_segment.ident.span());
+    } else if first_segment.ident == "super" {
+        path.segments.insert(
+            0,
+            syn::PathSegment::from(Ident::new("super", Span::call_site())),
+        );
+    }
+
+    path
+}
+
+/// Generates the derive attributes of the flags types, which are placed two modules down by
+/// [`generate_flags_module`].
+///
+/// Standard library derives are fully qualified, and any others are imported as `__derive{n}` one module down,
+/// which is also returned, to be resolved as written by the user.
+fn generate_flags_derives(
+    flags_derives: &[(CfgCondition, syn::Path)],
+) -> (Vec<Attribute>, Vec<TokenStream>) {
+    let mut derive_aliases = Vec::new();
+    let mut flags_attrs = Vec::with_capacity(flags_derives.len());
+    for (condition, path) in flags_derives {
+        let path = safe_flags_derive_path(path).unwrap_or_else(|| {
+            let alias = format_ident!("__derive{}", derive_aliases.len());
+            let path = path_from_child_module(path);
+            derive_aliases.push(quote!(use #path as #alias;));
+
+            syn::parse_quote!(super::#alias)
+        });
+
+        flags_attrs.push(condition.wrap(&quote!(derive(#path))));
+    }
+
+    (flags_attrs, derive_aliases)
+}
+
+/// Generates a private module containing the `bitflags!` invocations, and imports the flags types out of it.
+///
+/// The invocations are nested a second module down, which does not glob import the user's module, so constants
+/// there cannot be matched as patterns in the expansion of `bitflags!`. The module in between glob imports the
+/// user's module to import `bitflags` and the derives from, so they resolve as written by the user.
+fn generate_flags_module(
+    struct_name: &Ident,
+    flags_vis: &syn::Visibility,
+    derive_aliases: &[TokenStream],
+    bitflags_types: &[(&Ident, TokenStream)],
+) -> TokenStream {
+    let module = format_ident!("__{}_generated_flags", struct_name.unraw());
+    let (flags_names, invocations): (Vec<_>, Vec<_>) = bitflags_types.iter().cloned().unzip();
+
     #[cfg(feature = "typesize")]
-    let typesize_impl = Some(quote!(impl ::typesize::TypeSize for #flags_name {}));
+    let typesize_impls = Some(quote!(#(impl ::typesize::TypeSize for #flags_names {})*));
     #[cfg(not(feature = "typesize"))]
-    let typesize_impl: Option<TokenStream> = None;
+    let typesize_impls: Option<TokenStream> = None;
 
     quote!(
-        bitflags::bitflags! {
-            #(#flags_derives)*
-            pub(crate) struct #flags_name: #flags_size {
-                #(#flag_cfgs const #flag_names = #flag_values;)*
+        #[allow(non_snake_case)]
+        mod #module {
+            #[allow(unused_imports, clippy::wildcard_imports)]
+            use super::*;
+            use bitflags as __bitflags;
+            #(#derive_aliases)*
+
+            mod __flags {
+                #(#invocations)*
             }
+
+            #flags_vis use self::__flags::{#(#flags_names),*};
         }
 
-        #typesize_impl
+        #flags_vis use #module::{#(#flags_names),*};
+        #typesize_impls
     )
 }
 
+/// Generates the flags type of each group, or the checks against a user provided `storage` type.
+///
+/// Also returns the integer type of the bits of each group, or `None` if the group does not fit into one.
+fn generate_flags_defs(
+    struct_item: &syn::ItemStruct,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+    args: &Args,
+    flags_derives: &[(CfgCondition, syn::Path)],
+    errors: &mut Accumulator,
+) -> (TokenStream, Vec<Option<syn::Type>>) {
+    // `BoolPacked::Flags` exposes the flags type, so it must be public for public structs.
+    let flags_vis =
+        if args.runtime.is_some() && matches!(struct_item.vis, syn::Visibility::Public(_)) {
+            struct_item.vis.clone()
+        } else {
+            generate_pub_crate()
+        };
+
+    let (mut flags_attrs, derive_aliases) = generate_flags_derives(flags_derives);
+    if args.doc_hidden {
+        flags_attrs.push(syn::parse_quote!(#[doc(hidden)]));
+    }
+
+    let mut bitflags_defs = TokenStream::new();
+    let mut bitflags_types = Vec::new();
+    let mut bits_tys = Vec::with_capacity(groups.len());
+    for (index, group) in groups.iter().enumerate() {
+        let group_fields: Vec<_> = bool_fields.iter().filter(|f| f.group == index).collect();
+        // The `storage` argument only replaces the flags type of bools without a group.
+        let storage = args.storage.as_ref().filter(|_| group.name.is_none());
+        if let Some(storage) = storage {
+            bits_tys.push(Some(syn::parse_quote!(<#storage as bitflags::Flags>::Bits)));
+            bitflags_defs.extend(generate_storage_checks(storage, &group_fields));
+            continue;
+        }
+
+        let Some(flags_size) = errors.handle(get_flag_size(group, &group_fields)) else {
+            bits_tys.push(None);
+            continue;
+        };
+
+        bits_tys.push(Some(flags_size.clone()));
+        bitflags_types.push((
+            &group.flags_name,
+            generate_bitflags_type(
+                &struct_item.ident,
+                &flags_vis,
+                group,
+                &flags_size,
+                &group_fields,
+                &flags_attrs,
+            ),
+        ));
+    }
+
+    if !bitflags_types.is_empty() {
+        bitflags_defs.extend(generate_flags_module(
+            &struct_item.ident,
+            &flags_vis,
+            &derive_aliases,
+            &bitflags_types,
+        ));
+    }
+
+    (bitflags_defs, bits_tys)
+}
+
 pub fn bool_to_bitflags(
     args: TokenStream,
     mut struct_item: syn::ItemStruct,
 ) -> Result<TokenStream, Error> {
-    let args = Args::parse(args)?;
-
-    // Hidden flags type should not have the span of the struct's name.
-    let flag_field_name = Ident::new("__generated_flags", Span::call_site());
-    let flags_name = format_ident!(
-        "{}GeneratedFlags",
-        struct_item.ident,
-        span = Span::call_site()
-    );
+    let mut errors = Accumulator::default();
+
+    // Continue with the default arguments to find any other errors, skipping checks which depend on them.
+    let parsed_args = errors.handle(Args::parse(args));
+    let args_valid = parsed_args.is_some();
+    let args = parsed_args.unwrap_or_default();
 
     let mut original_struct = struct_item.clone();
     original_struct.ident = format_ident!("{}GeneratedOriginal", original_struct.ident);
+    generate_original_docs(&mut original_struct, &struct_item.ident, &args);
+    for field in &mut original_struct.fields {
+        field.attrs.retain(|attr| !FieldArgs::is_field_args(attr));
+    }
     strip_spans(&mut original_struct);
 
-    let flag_field = generate_flag_field(flags_name.clone(), flag_field_name.clone());
-    let (fields, bool_fields) = extract_bool_fields(flag_field, struct_item.fields)?;
+    if args_valid {
+        errors.handle(check_repr(&struct_item, &args));
+    }
+
+    let user_fields = struct_item.fields.clone();
+    let (fields, groups, bool_fields) =
+        extract_bool_fields(&struct_item.ident, &args, struct_item.fields, &mut errors)?;
+    if args_valid {
+        errors.handle(check_collisions(&user_fields, &groups, &bool_fields, &args));
+    }
     struct_item.fields = fields;
 
     let HijackOutput {
         compacted_struct_attrs,
         flags_derives,
-    } = hijack_derives(&mut struct_item, &original_struct.ident)?;
+        derives_typesize,
+        derives_default,
+        derives_partial_eq,
+    } = hijack_derives(&mut struct_item, &original_struct.ident, &args, &mut errors);
 
-    let from_impl = impl_from(
-        &struct_item,
-        &original_struct.ident,
-        &flag_field_name,
-        &flags_name,
-        &bool_fields,
-    );
+    let from_impl = impl_from(&struct_item, &original_struct.ident, &groups, &bool_fields);
 
-    let into_impl = impl_into(
-        &struct_item,
-        &original_struct.ident,
-        &flag_field_name,
-        &flags_name,
-        &bool_fields,
-    );
+    let into_impl = impl_into(&struct_item, &original_struct.ident, &groups, &bool_fields);
 
-    let flags_size = get_flag_size(bool_fields.len())?;
-    let bitflags_def =
-        generate_bitflags_type(&flags_name, &flags_size, &bool_fields, &flags_derives);
-    let func_impls = generate_getters_setters(
+    let (bitflags_defs, bits_tys) = generate_flags_defs(
         &struct_item,
-        &flags_name,
-        &flag_field_name,
+        &groups,
         &bool_fields,
         &args,
+        &flags_derives,
+        &mut errors,
     );
+    let func_impls = generate_getters_setters(&struct_item, &groups, &bool_fields, &args);
 
-    Ok(quote!(
+    if derives_default.is_empty() {
+        check_unused_defaults(&bool_fields, &mut errors);
+    } else {
+        strip_default_derive(&mut original_struct);
+    }
+
+    let default_impl = generate_derived(&derives_default, || {
+        impl_default(&struct_item, &original_struct, &bool_fields)
+    });
+
+    let bools_impl = impl_bools(&struct_item, &groups, &bool_fields, &args);
+    let bits_impl = impl_bits(&struct_item, &groups, &bits_tys, &bool_fields, &args);
+
+    errors.handle(check_compare_original(&args, &derives_partial_eq));
+    let partial_eq_impl = args
+        .compare_original
+        .then(|| {
+            generate_derived(&derives_partial_eq, || {
+                impl_partial_eq(&struct_item, &original_struct.ident, &groups, &bool_fields)
+            })
+        })
+        .flatten();
+
+    let typesize_impl = generate_derived(&derives_typesize, || {
+        impl_typesize(&struct_item, &groups, &bool_fields)
+    });
+
+    let runtime_impl = impl_runtime(&struct_item, &original_struct, &groups, &bool_fields, &args);
+
+    let bytes_saved_impl = impl_bytes_saved(&struct_item, &original_struct.ident, &args);
+    let size_checks = if args_valid {
+        errors.handle(generate_size_checks(
+            &struct_item,
+            &original_struct.ident,
+            &args,
+        ))
+    } else {
+        None
+    };
+
+    errors.finish()?;
+    let allow_unused = generate_allow_unused(&args);
+    let expansion = quote!(
         #[allow(clippy::struct_excessive_bools)]
+        #allow_unused
         #original_struct
         #from_impl
         #into_impl
 
-        #bitflags_def
+        #bitflags_defs
         #(#compacted_struct_attrs)*
         #struct_item
         #func_impls
-    ))
+        #bools_impl
+        #bits_impl
+        #default_impl
+        #partial_eq_impl
+        #typesize_impl
+        #runtime_impl
+        #bytes_saved_impl
+        #size_checks
+    );
+
+    if *args.debug {
+        write_expansion(&struct_item.ident, &expansion, args.debug.span())?;
+    }
+
+    Ok(expansion)
 }
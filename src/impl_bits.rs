@@ -0,0 +1,253 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{ext::IdentExt, ItemStruct};
+
+use crate::{
+    args::{Args, AttrList},
+    impl_get_set::{combined_accessor_vis, generate_method_attrs},
+    r#impl::{
+        extract_cfgs, generate_allow_unused, generate_pub_crate, least_restrictive_vis,
+        local_ident, most_restrictive_vis, BoolField, FlagGroup,
+    },
+};
+
+/// The names of the `bits`, `try_set_bits`, `set_bits_truncate`, and `set_bits_unchecked` methods of a group.
+pub fn bits_method_names(group_name: Option<&Ident>) -> [Ident; 4] {
+    match group_name {
+        Some(name) => [
+            format_ident!("{}_bits", name),
+            format_ident!("try_set_{}_bits", name),
+            format_ident!("set_{}_bits_truncate", name),
+            format_ident!("set_{}_bits_unchecked", name),
+        ],
+        None => [
+            format_ident!("bits"),
+            format_ident!("try_set_bits"),
+            format_ident!("set_bits_truncate"),
+            format_ident!("set_bits_unchecked"),
+        ],
+    }
+}
+
+/// Generates the `{StructName}InvalidBits` error type, returned by `try_set_bits`.
+///
+/// `error_vis` is the visibility of the most visible `try_set_bits`, so the names in `UntaggedValue` are only
+/// returned to code which can already access those fields.
+fn generate_error_type(
+    struct_item: &ItemStruct,
+    error_name: &Ident,
+    error_vis: &syn::Visibility,
+    allow_unused: Option<&TokenStream>,
+) -> TokenStream {
+    let struct_name = &struct_item.ident;
+    let error_docs =
+        format!("The error returned when setting the bits of [`{struct_name}`] fails.");
+
+    quote!(
+        #[doc = #error_docs]
+        #[derive(
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::fmt::Debug,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+        )]
+        #allow_unused
+        #error_vis enum #error_name {
+            /// The bits contain bits which do not belong to any flag.
+            UnknownBits,
+            /// The value bit of an `Option<bool>` field is set without its tag bit.
+            UntaggedValue {
+                /// The name of the field.
+                field: &'static str,
+            },
+        }
+
+        impl ::core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    Self::UnknownBits => f.write_str("bits do not belong to any flag"),
+                    Self::UntaggedValue { field } => {
+                        ::core::write!(f, "the value bit of `{}` is set without its tag bit", field)
+                    }
+                }
+            }
+        }
+    )
+}
+
+/// Generates the raw bits accessors of `group`, which hold the packed fields in `group_fields`.
+///
+/// Also returns the visibility of the setters, which return the error type.
+fn generate_group_bits(
+    group: &FlagGroup,
+    bits_ty: &syn::Type,
+    group_fields: &[&BoolField],
+    error_name: &Ident,
+    args: &Args,
+    struct_vis: &syn::Visibility,
+) -> (TokenStream, syn::Visibility) {
+    let flags_name = &group.flags_path;
+    let [getter_name, try_setter_name, truncate_name, unchecked_name] =
+        bits_method_names(group.name.as_ref());
+    let [flags, bits] = ["flags", "bits"].map(local_ident);
+    let self_ = quote!(self);
+
+    // A user provided `storage` type may not be as visible as the struct, which is exposed by `bits_ty`.
+    let storage = group.name.is_none() && args.storage.is_some();
+    let storage_vis = storage.then(generate_pub_crate);
+    let fields = group_fields.iter().copied();
+    let [getter_vis, setter_vis] = [args.private_getters, args.private_setters].map(|private| {
+        let vis = combined_accessor_vis(struct_vis, fields.clone(), private);
+        most_restrictive_vis(std::iter::once(&vis).chain(&storage_vis))
+    });
+
+    let group_desc = match &group.name {
+        Some(group_name) => format!("the packed flags of the `{group_name}` group"),
+        None => String::from("the packed flags"),
+    };
+
+    let getter_docs = format!("Returns the raw bits of {group_desc}, to be stored elsewhere.");
+    let try_setter_docs = format!(
+        "Replaces {group_desc} with `bits`, such as those returned by [`Self::{getter_name}`].\n\n\
+        Errors without changing any fields if `bits` contains unknown bits, or the value bit of an \
+        `Option<bool>` field without its tag bit."
+    );
+    let truncate_docs = format!(
+        "Replaces {group_desc} with `bits`, dropping unknown bits and the value bits of `Option<bool>` fields \
+        without their tag bit."
+    );
+    let unchecked_docs = format!(
+        "Replaces {group_desc} with `bits`, without any validation.\n\n\
+        # Safety\n\
+        `bits` must be accepted by [`Self::{try_setter_name}`], as code may rely on the packed fields only \
+        storing valid bits."
+    );
+
+    let opt_fields = group_fields
+        .iter()
+        .filter_map(|field| Some((*field, field.tag_bit_flag_ident()?)));
+
+    let validation = opt_fields.clone().map(|(field, tag_bit_flag_ident)| {
+        let cfgs = extract_cfgs(&field.attrs);
+        let flag_name = &field.flag_ident;
+        let field_name = field.field_ident.unraw().to_string();
+        quote!(
+            #(#cfgs)*
+            if #flags.contains(#flags_name::#flag_name) && !#flags.contains(#flags_name::#tag_bit_flag_ident) {
+                return ::core::result::Result::Err(#error_name::UntaggedValue { field: #field_name });
+            }
+        )
+    });
+
+    let truncation: Vec<_> = opt_fields
+        .map(|(field, tag_bit_flag_ident)| {
+            let cfgs = extract_cfgs(&field.attrs);
+            let flag_name = &field.flag_ident;
+            quote!(
+                #(#cfgs)*
+                if !#flags.contains(#flags_name::#tag_bit_flag_ident) {
+                    #flags.remove(#flags_name::#flag_name);
+                }
+            )
+        })
+        .collect();
+
+    let flags_mut = (!truncation.is_empty()).then(|| quote!(mut));
+    let getter_body = group.flag_field.read(&self_);
+    let store = group.flag_field.store(&self_, &quote!(#flags));
+    let self_ty = if args.cell {
+        quote!(&self)
+    } else {
+        quote!(&mut self)
+    };
+
+    let getter_attrs = generate_method_attrs(true, &AttrList::default());
+    let setter_attrs = generate_method_attrs(false, &AttrList::default());
+
+    let accessors = quote!(
+        #[doc = #getter_docs]
+        #getter_attrs
+        #getter_vis fn #getter_name(&self) -> #bits_ty {
+            #getter_body.bits()
+        }
+
+        #[doc = #try_setter_docs]
+        #setter_attrs
+        #setter_vis fn #try_setter_name(#self_ty, #bits: #bits_ty) -> ::core::result::Result<(), #error_name> {
+            let ::core::option::Option::Some(#flags) = #flags_name::from_bits(#bits) else {
+                return ::core::result::Result::Err(#error_name::UnknownBits);
+            };
+
+            #(#validation)*
+            #store
+            ::core::result::Result::Ok(())
+        }
+
+        #[doc = #truncate_docs]
+        #setter_attrs
+        #setter_vis fn #truncate_name(#self_ty, #bits: #bits_ty) {
+            let #flags_mut #flags = #flags_name::from_bits_truncate(#bits);
+            #(#truncation)*
+            #store
+        }
+
+        #[doc = #unchecked_docs]
+        #setter_attrs
+        #setter_vis unsafe fn #unchecked_name(#self_ty, #bits: #bits_ty) {
+            let #flags = #flags_name::from_bits_retain(#bits);
+            #store
+        }
+    );
+
+    (accessors, setter_vis)
+}
+
+/// Generates the raw bits accessors of every group, and their error type.
+///
+/// `bits_tys` holds the integer type of each group, with groups which failed to fit skipped.
+pub fn impl_bits(
+    struct_item: &ItemStruct,
+    groups: &[FlagGroup],
+    bits_tys: &[Option<syn::Type>],
+    bool_fields: &[BoolField],
+    args: &Args,
+) -> TokenStream {
+    let struct_name = &struct_item.ident;
+    let (impl_generics, ty_generics, where_clause) = struct_item.generics.split_for_impl();
+    let error_name = format_ident!("{}InvalidBits", struct_name);
+    let allow_unused = generate_allow_unused(args);
+
+    let mut impl_body = TokenStream::new();
+    let mut setter_vises = Vec::with_capacity(groups.len());
+    for (index, (group, bits_ty)) in groups.iter().zip(bits_tys).enumerate() {
+        let Some(bits_ty) = bits_ty else {
+            continue;
+        };
+
+        let group_fields: Vec<_> = bool_fields.iter().filter(|f| f.group == index).collect();
+        let (accessors, setter_vis) = generate_group_bits(
+            group,
+            bits_ty,
+            &group_fields,
+            &error_name,
+            args,
+            &struct_item.vis,
+        );
+
+        impl_body.extend(accessors);
+        setter_vises.push(setter_vis);
+    }
+
+    let error_vis = least_restrictive_vis(&setter_vises);
+    let error_def =
+        generate_error_type(struct_item, &error_name, &error_vis, allow_unused.as_ref());
+    quote!(
+        #error_def
+
+        #allow_unused
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #impl_body
+        }
+    )
+}
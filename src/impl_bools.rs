@@ -0,0 +1,106 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::ItemStruct;
+
+use crate::{
+    args::{Args, AttrList},
+    impl_get_set::{
+        combined_accessor_vis, extract_docs_or, generate_getter_body, generate_method_attrs,
+        generate_setter_body, handle_owning_setters, setters_are_shared,
+    },
+    r#impl::{extract_cfgs, generate_allow_unused, local_ident, BoolField, FlagGroup},
+};
+
+pub fn impl_bools(
+    struct_item: &ItemStruct,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+    args: &Args,
+) -> TokenStream {
+    let struct_name = &struct_item.ident;
+    // The snapshot exposes every packed field, so cannot be more visible than any of them.
+    let bools_vis = combined_accessor_vis(&struct_item.vis, bool_fields, false);
+    let getter_vis = combined_accessor_vis(&struct_item.vis, bool_fields, args.private_getters);
+    let setter_vis = combined_accessor_vis(&struct_item.vis, bool_fields, args.private_setters);
+    let bools_name = format_ident!("{}Bools", struct_name);
+    let (impl_generics, ty_generics, where_clause) = struct_item.generics.split_for_impl();
+
+    let bools_fields = bool_fields.iter().map(|field| {
+        let field_name = &field.field_ident;
+        let field_docs = extract_docs_or(
+            &field.attrs,
+            &format!("The {} of [`{struct_name}`].", field_name.unraw()),
+        );
+        let cfgs = extract_cfgs(&field.attrs);
+        let value_ty = field.value_ty();
+
+        quote!(#field_docs #(#cfgs)* #bools_vis #field_name: #value_ty)
+    });
+
+    let getters = bool_fields.iter().map(|field| {
+        let field_name = &field.field_ident;
+        let cfgs = extract_cfgs(&field.attrs);
+        let group = field.group(groups);
+        let flags = group.flag_field.read(&quote!(self));
+        let getter_body = generate_getter_body(field, &flags, &group.flags_path);
+
+        quote!(#(#cfgs)* #field_name: #getter_body)
+    });
+
+    let bools = local_ident("bools");
+    let setters = bool_fields.iter().map(|field| {
+        let field_name = &field.field_ident;
+        let cfgs = extract_cfgs(&field.attrs);
+        let group = field.group(groups);
+        let shared = setters_are_shared(args);
+        let setter_body = group.flag_field.update(&quote!(self), shared, |flags| {
+            generate_setter_body(field, flags, &group.flags_path, &quote!(#bools.#field_name))
+        });
+
+        quote!(#(#cfgs)* { #setter_body })
+    });
+
+    let (setter_self_ty, setter_ret_ty, setter_ret) = handle_owning_setters(args);
+    let getter_attrs = generate_method_attrs(true, &AttrList::default());
+    let setter_attrs = generate_method_attrs(args.owning_setters, &AttrList::default());
+    let bools_docs = format!(
+        "A copy of every packed bool in [`{struct_name}`], to allow destructuring and pattern matching."
+    );
+
+    let allow_unused = generate_allow_unused(args);
+    quote!(
+        #[doc = #bools_docs]
+        #[derive(
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::fmt::Debug,
+            ::core::default::Default,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash,
+        )]
+        #allow_unused
+        #bools_vis struct #bools_name {
+            #(#bools_fields,)*
+        }
+
+        #allow_unused
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Returns a copy of every packed bool.
+            #getter_attrs
+            #getter_vis fn bools(&self) -> #bools_name {
+                #bools_name {
+                    #(#getters,)*
+                }
+            }
+
+            /// Sets every packed bool to the values provided.
+            #setter_attrs
+            #setter_vis fn set_bools(#setter_self_ty, #bools: #bools_name) -> #setter_ret_ty {
+                #(#setters)*
+                #setter_ret
+            }
+        }
+    )
+}
@@ -0,0 +1,107 @@
+use std::borrow::Cow;
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{punctuated::Punctuated, spanned::Spanned, Attribute, ItemStruct, Token};
+
+use crate::{
+    error::{Accumulator, Error},
+    impl_from_into::extract_fields,
+    r#impl::{add_trait_bounds, extract_cfgs, local_ident, BoolField},
+};
+
+/// Finds the default value from `#[serde(default)]` or `#[serde(default = "path")]`, if present.
+fn extract_serde_default(attrs: &[Attribute]) -> Option<TokenStream> {
+    let serde_attrs = attrs.iter().filter(|attr| attr.path().is_ident("serde"));
+    for attr in serde_attrs {
+        let parser = Punctuated::<syn::Meta, Token![,]>::parse_terminated;
+        let Ok(metas) = attr.parse_args_with(parser) else {
+            continue;
+        };
+
+        for meta in metas {
+            match meta {
+                syn::Meta::Path(path) if path.is_ident("default") => {
+                    return Some(quote!(::core::default::Default::default()));
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("default") => {
+                    let syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(default_fn),
+                        ..
+                    }) = name_value.value
+                    else {
+                        continue;
+                    };
+
+                    let default_fn = default_fn.parse::<syn::ExprPath>().ok()?;
+                    return Some(quote!(#default_fn()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    None
+}
+
+pub fn impl_default(
+    struct_item: &ItemStruct,
+    original_struct: &ItemStruct,
+    bool_fields: &[BoolField],
+) -> TokenStream {
+    let struct_name = &struct_item.ident;
+    let original_struct_name = &original_struct.ident;
+
+    let generics = add_trait_bounds(
+        &struct_item.generics,
+        &syn::parse_quote!(::core::default::Default),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let fields = extract_fields(&original_struct.fields);
+    let field_defaults = fields.iter().map(|field| {
+        let field_name = field.ident.as_ref().expect("fields are named");
+        let cfgs = extract_cfgs(&field.attrs);
+
+        let bool_field = bool_fields.iter().find(|f| &f.field_ident == field_name);
+        let bitflags_default = bool_field.and_then(|f| f.args.default.as_ref());
+
+        let default = bitflags_default
+            .map(ToTokens::to_token_stream)
+            .or_else(|| extract_serde_default(&field.attrs))
+            .unwrap_or_else(|| quote!(::core::default::Default::default()));
+
+        quote!(#(#cfgs)* #field_name: #default)
+    });
+
+    let original = local_ident("original");
+    quote!(
+        #[allow(deprecated)]
+        impl #impl_generics ::core::default::Default for #original_struct_name #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#field_defaults,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::default::Default for #struct_name #ty_generics #where_clause {
+            fn default() -> Self {
+                let #original = <#original_struct_name #ty_generics as ::core::default::Default>::default();
+                <Self as ::core::convert::From<#original_struct_name #ty_generics>>::from(#original)
+            }
+        }
+    )
+}
+
+/// Errors for every field with a default value set, if the struct doesn't derive `Default`.
+pub fn check_unused_defaults(bool_fields: &[BoolField], errors: &mut Accumulator) {
+    for default in bool_fields.iter().filter_map(|f| f.args.default.as_ref()) {
+        errors.push(Error::Custom(
+            default.span(),
+            Cow::Borrowed(
+                "bool_to_bitflags: Field defaults require the struct to derive `Default`!",
+            ),
+        ));
+    }
+}
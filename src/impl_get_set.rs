@@ -2,17 +2,59 @@ use std::borrow::Cow;
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
+use syn::ext::IdentExt;
 
 use crate::{
-    args::Args,
-    r#impl::{extract_cfgs, generate_pub_crate, ty_from_ident, BoolField},
+    args::{Args, AttrList},
+    cfg_attr::expand_cfg_attrs,
+    r#impl::{
+        extract_cfgs, generate_allow_unused, generate_pub_crate, ident_or_raw, local_ident,
+        most_restrictive_vis, ty_from_ident, BoolField, FlagGroup,
+    },
 };
 
-fn extract_docs(attrs: &[syn::Attribute]) -> TokenStream {
-    let attrs = attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+/// Extracts the doc comments, `#[doc = "..."]`, from `attrs`.
+pub fn extract_docs(attrs: &[syn::Attribute]) -> TokenStream {
+    let attrs = attrs
+        .iter()
+        .filter(|attr| matches!(&attr.meta, syn::Meta::NameValue(nv) if nv.path.is_ident("doc")));
     quote!(#(#attrs)*)
 }
 
+/// Lint level attributes which are forwarded from packed fields to their accessors.
+const FORWARDED_LINT_ATTRS: &[&str] = &["allow", "warn", "deny", "forbid"];
+
+/// Extracts the field attributes which are forwarded to every accessor of a packed field.
+///
+/// These are `deprecated`, lint levels, and non-comment `doc` attributes such as `#[doc(hidden)]`,
+/// which are kept under the same conditions if applied via `cfg_attr`.
+fn extract_forwarded_attrs(attrs: &[syn::Attribute]) -> TokenStream {
+    let attrs = expand_cfg_attrs(attrs)
+        .into_iter()
+        .filter(|(_, meta)| {
+            let path = meta.path();
+            if path.is_ident("doc") {
+                return !matches!(meta, syn::Meta::NameValue(_));
+            }
+
+            path.is_ident("deprecated")
+                || FORWARDED_LINT_ATTRS.iter().any(|lint| path.is_ident(lint))
+        })
+        .map(|(condition, meta)| condition.wrap(&meta));
+
+    quote!(#(#attrs)*)
+}
+
+/// Extracts the docs from `attrs`, falling back to `fallback` so generated items are never undocumented.
+pub fn extract_docs_or(attrs: &[syn::Attribute], fallback: &str) -> TokenStream {
+    let docs = extract_docs(attrs);
+    if docs.is_empty() {
+        quote!(#[doc = #fallback])
+    } else {
+        docs
+    }
+}
+
 fn handle_visibility_arg(field_vis: &syn::Visibility, private: bool) -> Cow<'_, syn::Visibility> {
     if private {
         Cow::Owned(generate_pub_crate())
@@ -21,9 +63,38 @@ fn handle_visibility_arg(field_vis: &syn::Visibility, private: bool) -> Cow<'_,
     }
 }
 
-fn handle_owning_setters(owning_setters: bool) -> (TokenStream, syn::Type, Option<Ident>) {
+/// The visibility of an accessor which touches every field in `fields`, no wider than any of them or the struct.
+///
+/// If `private` is true, from `private_getters` or `private_setters`, the accessor is also at most `pub(crate)`.
+pub fn combined_accessor_vis<'a>(
+    struct_vis: &syn::Visibility,
+    fields: impl IntoIterator<Item = &'a BoolField>,
+    private: bool,
+) -> syn::Visibility {
+    let pub_crate = generate_pub_crate();
+    let field_vises = fields.into_iter().map(|field| &field.vis);
+    let private_vis = private.then_some(&pub_crate);
+
+    most_restrictive_vis(
+        std::iter::once(struct_vis)
+            .chain(field_vises)
+            .chain(private_vis),
+    )
+}
+
+/// Generates `#[must_use]` (if `must_use`) and `#[inline]`, followed by `user_attrs` which can replace them.
+pub fn generate_method_attrs(must_use: bool, user_attrs: &AttrList) -> TokenStream {
+    let has_attr = |name| user_attrs.0.iter().any(|attr| attr.path().is_ident(name));
+    let must_use = (must_use && !has_attr("must_use")).then(|| quote!(#[must_use]));
+    let inline = (!has_attr("inline")).then(|| quote!(#[inline]));
+
+    let user_attrs = &user_attrs.0;
+    quote!(#must_use #inline #(#[#user_attrs])*)
+}
+
+pub fn handle_owning_setters(args: &Args) -> (TokenStream, syn::Type, Option<Ident>) {
     let self_ident = Ident::new("self", Span::call_site());
-    if owning_setters {
+    if args.owning_setters {
         let owned_self_ty = ty_from_ident(Ident::new("Self", Span::call_site()));
         (quote!(mut self), owned_self_ty, Some(self_ident))
     } else {
@@ -32,36 +103,160 @@ fn handle_owning_setters(owning_setters: bool) -> (TokenStream, syn::Type, Optio
             elems: syn::punctuated::Punctuated::new(),
         });
 
-        (quote!(&mut self), unit_ret, None)
+        if args.cell {
+            (quote!(&self), unit_ret, None)
+        } else {
+            (quote!(&mut self), unit_ret, None)
+        }
     }
 }
 
-fn args_to_names(args: &Args, field_name: &Ident) -> (Ident, Ident) {
+/// If setters only take `&self`, from the `cell` argument.
+pub fn setters_are_shared(args: &Args) -> bool {
+    args.cell && !args.owning_setters
+}
+
+pub fn args_to_names(args: &Args, field_name: &Ident) -> (Ident, Ident) {
     let getter_prefix = args.getter_prefix.as_deref().unwrap_or("");
     let setter_prefix = args.setter_prefix.as_deref().unwrap_or("set_");
     let span = field_name.span();
+    let field_name = field_name.unraw();
 
     (
-        format_ident!("{getter_prefix}{field_name}", span = span),
-        format_ident!("{setter_prefix}{field_name}", span = span),
+        ident_or_raw(&format!("{getter_prefix}{field_name}"), span),
+        ident_or_raw(&format!("{setter_prefix}{field_name}"), span),
     )
 }
 
 pub fn generate_getter_body(
     field: &BoolField,
-    flag_field: &Ident,
-    flags_name: &Ident,
+    flags: &TokenStream,
+    flags_name: &syn::Path,
 ) -> TokenStream {
     let flag_name = &field.flag_ident;
+    let invert = field.args.inverted.then(|| quote!(!));
     match field {
-        BoolField::Normal(..) => quote!(self.#flag_field.contains(#flags_name::#flag_name)),
+        BoolField::Normal(..) => quote!(#invert #flags.contains(#flags_name::#flag_name)),
         BoolField::Opt {
             tag_bit_flag_ident, ..
         } => quote!(
-            if self.#flag_field.contains(#flags_name::#tag_bit_flag_ident) {
-                Some(self.#flag_field.contains(#flags_name::#flag_name))
+            if #flags.contains(#flags_name::#tag_bit_flag_ident) {
+                ::core::option::Option::Some(#invert #flags.contains(#flags_name::#flag_name))
             } else {
-                None
+                ::core::option::Option::None
+            }
+        ),
+    }
+}
+
+/// Generates the statements to store `value` into `flags`, the inverse of [`generate_getter_body`].
+pub fn generate_setter_body(
+    field: &BoolField,
+    flags: &TokenStream,
+    flags_name: &syn::Path,
+    value: &TokenStream,
+) -> TokenStream {
+    let flag_name = &field.flag_ident;
+    let invert = field.args.inverted.then(|| quote!(!));
+    match field {
+        BoolField::Normal(..) => quote!(#flags.set(#flags_name::#flag_name, #invert #value);),
+        BoolField::Opt {
+            tag_bit_flag_ident, ..
+        } => {
+            let inner_value = local_ident("value");
+            quote!(
+                if let ::core::option::Option::Some(#inner_value) = #value {
+                    #flags.insert(#flags_name::#tag_bit_flag_ident);
+                    #flags.set(#flags_name::#flag_name, #invert #inner_value);
+                } else {
+                    #flags.remove(#flags_name::#tag_bit_flag_ident);
+                    #flags.remove(#flags_name::#flag_name);
+                }
+            )
+        }
+    }
+}
+
+/// Generates the `{group}_flags` and `clear_{group}` accessors for each named group.
+fn generate_group_accessors(
+    struct_item: &syn::ItemStruct,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+    args: &Args,
+) -> TokenStream {
+    let (setter_self_ty, setter_ret_ty, setter_ret) = handle_owning_setters(args);
+    let self_ = quote!(self);
+
+    let mut accessors = TokenStream::new();
+    for (index, group) in groups.iter().enumerate() {
+        let Some(group_name) = &group.name else {
+            continue;
+        };
+
+        let group_fields = bool_fields.iter().filter(|f| f.group == index);
+        // The flags type is `pub(crate)`, so the flags getter cannot be any more visible.
+        let flags_vis = combined_accessor_vis(&struct_item.vis, group_fields.clone(), true);
+        let clear_vis =
+            combined_accessor_vis(&struct_item.vis, group_fields.clone(), args.private_setters);
+
+        // Inverted `bool` fields read `false` when their flag is set, so have it set again after clearing.
+        let inverted_fields: Vec<_> = group_fields
+            .filter(|field| matches!(field, BoolField::Normal(..)) && field.args.inverted)
+            .map(|field| (extract_cfgs(&field.attrs), &field.flag_ident))
+            .collect();
+
+        let flags_name = &group.flags_path;
+        let flags_getter = format_ident!("{}_flags", group_name);
+        let clear_name = format_ident!("clear_{}", group_name);
+
+        let flags_docs = format!("Returns a copy of the packed flags of the `{group_name}` group.");
+        let clear_docs = format!(
+            "Sets every field of the `{group_name}` group to `false`, or `None` for `Option<bool>` fields, without touching other groups."
+        );
+
+        let flags_read = group.flag_field.read(&self_);
+        let flags_body = quote!(#flags_name::from_bits_retain(#flags_read.bits()));
+        let clear_body = group
+            .flag_field
+            .update(&self_, setters_are_shared(args), |flags| {
+                let inserts = inverted_fields.iter().map(
+                    |(cfgs, flag_ident)| quote!(#(#cfgs)* #flags.insert(#flags_name::#flag_ident);),
+                );
+
+                quote!(#flags.remove(#flags_name::all()); #(#inserts)*)
+            });
+
+        let flags_attrs = generate_method_attrs(true, &AttrList::default());
+        let clear_attrs = generate_method_attrs(args.owning_setters, &AttrList::default());
+
+        accessors.extend(quote!(
+            #[doc = #flags_docs]
+            #flags_attrs
+            #flags_vis fn #flags_getter(&self) -> #flags_name {
+                #flags_body
+            }
+
+            #[doc = #clear_docs]
+            #clear_attrs
+            #clear_vis fn #clear_name(#setter_self_ty) -> #setter_ret_ty {
+                #clear_body
+                #setter_ret
+            }
+        ));
+    }
+
+    accessors
+}
+
+/// Generates the body of `{getter}_ref`, which borrows a `static` with the value of `getter_body`.
+fn generate_ref_body(field: &BoolField, getter_body: &TokenStream) -> TokenStream {
+    match field {
+        BoolField::Normal(_) => quote!(if #getter_body { &true } else { &false }),
+        BoolField::Opt { .. } => quote!(
+            match #getter_body {
+                ::core::option::Option::Some(true) => &::core::option::Option::Some(true),
+                ::core::option::Option::Some(false) => &::core::option::Option::Some(false),
+                ::core::option::Option::None => &::core::option::Option::None,
             }
         ),
     }
@@ -69,81 +264,150 @@ pub fn generate_getter_body(
 
 pub fn generate_getters_setters(
     struct_item: &syn::ItemStruct,
-    flags_name: &Ident,
-    flag_field: &Ident,
+    groups: &[FlagGroup],
     bool_fields: &[BoolField],
     args: &Args,
 ) -> TokenStream {
     let struct_name = &struct_item.ident;
     let (impl_generics, ty_generics, where_clause) = struct_item.generics.split_for_impl();
 
-    let mut impl_body = TokenStream::new();
+    let self_ = quote!(self);
+    let (value, flags) = (local_ident("value"), local_ident("flags"));
+    let guard_name = format_ident!("{}GeneratedGuard", struct_name);
+    let allow_unused = generate_allow_unused(args);
+    let guard_def = generate_guard(&guard_name, allow_unused.as_ref());
+
+    let mut impl_body = generate_group_accessors(struct_item, groups, bool_fields, args);
     for field in bool_fields {
+        let group = field.group(groups);
+        let (flag_field, flags_name) = (&group.flag_field, &group.flags_path);
+        let flags_mut = flag_field.get_mut(&self_);
+
         let field_cfgs = extract_cfgs(&field.attrs);
-        let field_cfgs_clone = field_cfgs.clone();
+        let forwarded_attrs = extract_forwarded_attrs(&field.attrs);
+        let field_attrs = quote!(#(#field_cfgs)* #forwarded_attrs);
 
-        let field_docs = extract_docs(&field.attrs);
         let field_name = &field.field_ident;
-        let flag_name = &field.flag_ident;
+        let field_desc = field_name.unraw();
 
         let getter_vis = handle_visibility_arg(&field.vis, args.private_getters);
         let setter_vis = handle_visibility_arg(&field.vis, args.private_setters);
-        let (setter_self_ty, setter_ret_ty, setter_ret) =
-            handle_owning_setters(args.owning_setters);
+        let (setter_self_ty, setter_ret_ty, setter_ret) = handle_owning_setters(args);
 
         let (getter_name, setter_name) = args_to_names(args, field_name);
+        let getter_fallback = format!("Returns the {field_desc}.");
+        let setter_fallback = format!("Sets the {field_desc} to the value provided.");
         let (getter_docs, setter_docs) = if args.document_setters {
-            (TokenStream::default(), field_docs)
+            (
+                quote!(#[doc = #getter_fallback]),
+                extract_docs_or(&field.attrs, &setter_fallback),
+            )
         } else {
-            let setter_docs = format!("Sets the {field_name} to the value provided.");
-            (field_docs, quote!(#[doc = #setter_docs]))
+            (
+                extract_docs_or(&field.attrs, &getter_fallback),
+                quote!(#[doc = #setter_fallback]),
+            )
         };
 
-        let getter_body = generate_getter_body(field, flag_field, flags_name);
-        let to_extend = match field {
-            BoolField::Normal(_) => quote!(
-                #getter_docs
-                #(#field_cfgs)*
-                #getter_vis fn #getter_name(&self) -> bool {
-                    #getter_body
-                }
+        let (mut_name, ref_name) = (
+            format_ident!("{}_mut", getter_name),
+            format_ident!("{}_ref", getter_name),
+        );
 
-                #setter_docs
-                #(#field_cfgs_clone)*
-                #setter_vis fn #setter_name(#setter_self_ty, value: bool) -> #setter_ret_ty {
-                    self.#flag_field.set(#flags_name::#flag_name, value);
-                    #setter_ret
-                }
-            ),
-            BoolField::Opt {
-                tag_bit_flag_ident, ..
-            } => quote!(
-                #getter_docs
-                #(#field_cfgs)*
-                #getter_vis fn #getter_name(&self) -> Option<bool> {
-                    #getter_body
-                }
+        let mut_docs =
+            format!("Returns a guard to mutate the {field_desc}, which is written back on drop.");
+        let ref_docs =
+            format!("Returns a reference to the {field_desc}, pointing to a `static` value.");
 
-                #setter_docs
-                #(#field_cfgs_clone)*
-                #setter_vis fn #setter_name(#setter_self_ty, value: Option<bool>) -> #setter_ret_ty {
-                    if let Some(value) = value {
-                        self.#flag_field.insert(#flags_name::#tag_bit_flag_ident);
-                        self.#flag_field.set(#flags_name::#flag_name, value);
-                    } else {
-                        self.#flag_field.remove(#flags_name::#tag_bit_flag_ident);
-                    };
-                    #setter_ret
-                }
-            ),
-        };
+        let value_ty = field.value_ty();
+        let getter_body = generate_getter_body(field, &flag_field.read(&self_), flags_name);
+        let setter_body = flag_field.update(&self_, setters_are_shared(args), |flags| {
+            generate_setter_body(field, flags, flags_name, &quote!(#value))
+        });
+        let guard_setter_body =
+            generate_setter_body(field, &quote!(#flags), flags_name, &quote!(#value));
+        let ref_body = generate_ref_body(field, &getter_body);
+
+        let getter_attrs = generate_method_attrs(true, &args.getter_attrs);
+        let setter_attrs = generate_method_attrs(args.owning_setters, &args.setter_attrs);
+        let accessor_attrs = generate_method_attrs(true, &AttrList::default());
 
-        impl_body.extend([to_extend]);
+        impl_body.extend(quote!(
+            #getter_docs
+            #getter_attrs
+            #field_attrs
+            #getter_vis fn #getter_name(&self) -> #value_ty {
+                #getter_body
+            }
+
+            #setter_docs
+            #setter_attrs
+            #field_attrs
+            #setter_vis fn #setter_name(#setter_self_ty, #value: #value_ty) -> #setter_ret_ty {
+                #setter_body
+                #setter_ret
+            }
+
+            #[doc = #ref_docs]
+            #accessor_attrs
+            #field_attrs
+            #getter_vis fn #ref_name(&self) -> &'static #value_ty {
+                #ref_body
+            }
+
+            #[doc = #mut_docs]
+            #accessor_attrs
+            #field_attrs
+            #setter_vis fn #mut_name(&mut self) -> impl ::core::ops::DerefMut<Target = #value_ty> + '_ {
+                #guard_name {
+                    value: #getter_body,
+                    write: |#flags: &mut #flags_name, #value: #value_ty| { #guard_setter_body },
+                    flags: #flags_mut,
+                }
+            }
+        ));
     }
 
     quote!(
+        #guard_def
+
+        #allow_unused
         impl #impl_generics #struct_name #ty_generics #where_clause {
             #impl_body
         }
     )
 }
+
+/// Generates a guard type for `*_mut` methods, as packed fields cannot be borrowed directly.
+///
+/// This is generic over the flags type `F`, as each group has its own flags type.
+fn generate_guard(guard_name: &Ident, allow_unused: Option<&TokenStream>) -> TokenStream {
+    quote!(
+        /// Writes a packed field back into the flags when dropped, returned by the `*_mut` methods.
+        #allow_unused
+        struct #guard_name<'a, F, T: ::core::marker::Copy> {
+            flags: &'a mut F,
+            value: T,
+            write: fn(&mut F, T),
+        }
+
+        impl<F, T: ::core::marker::Copy> ::core::ops::Deref for #guard_name<'_, F, T> {
+            type Target = T;
+            fn deref(&self) -> &T {
+                &self.value
+            }
+        }
+
+        impl<F, T: ::core::marker::Copy> ::core::ops::DerefMut for #guard_name<'_, F, T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.value
+            }
+        }
+
+        impl<F, T: ::core::marker::Copy> ::core::ops::Drop for #guard_name<'_, F, T> {
+            fn drop(&mut self) {
+                (self.write)(self.flags, self.value);
+            }
+        }
+    )
+}
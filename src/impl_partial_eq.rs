@@ -0,0 +1,77 @@
+use std::borrow::Cow;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::ItemStruct;
+
+use crate::{
+    args::Args,
+    cfg_attr::CfgCondition,
+    error::Error,
+    impl_from_into::{extract_fields, extract_passthrough_fields},
+    impl_get_set::generate_getter_body,
+    r#impl::{add_trait_bounds, extract_cfgs, local_ident, BoolField, FlagGroup},
+};
+
+/// Errors if `compare_original` is passed, but the struct does not derive `PartialEq`.
+pub fn check_compare_original(
+    args: &Args,
+    derives_partial_eq: &[CfgCondition],
+) -> Result<(), Error> {
+    if *args.compare_original && derives_partial_eq.is_empty() {
+        return Err(Error::Custom(
+            args.compare_original.span(),
+            Cow::Borrowed(
+                "bool_to_bitflags: `compare_original` requires the struct to derive `PartialEq`!",
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn impl_partial_eq(
+    struct_item: &ItemStruct,
+    original_struct_name: &Ident,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+) -> TokenStream {
+    let struct_name = &struct_item.ident;
+    let generics = add_trait_bounds(
+        &struct_item.generics,
+        &syn::parse_quote!(::core::cmp::PartialEq),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let other = local_ident("other");
+    let fields = extract_fields(&struct_item.fields);
+    let passthrough_checks = extract_passthrough_fields(fields, groups)
+        .map(|(ident, cfgs)| quote!(#(#cfgs)* if self.#ident != #other.#ident { return false; }));
+
+    let bool_checks = bool_fields.iter().map(|field| {
+        let field_name = &field.field_ident;
+        let cfgs = extract_cfgs(&field.attrs);
+        let group = field.group(groups);
+        let flags = group.flag_field.read(&quote!(self));
+        let getter_body = generate_getter_body(field, &flags, &group.flags_path);
+
+        quote!(#(#cfgs)* if (#getter_body) != #other.#field_name { return false; })
+    });
+
+    quote!(
+        #[allow(deprecated)]
+        impl #impl_generics ::core::cmp::PartialEq<#original_struct_name #ty_generics> for #struct_name #ty_generics #where_clause {
+            fn eq(&self, #other: &#original_struct_name #ty_generics) -> bool {
+                #(#passthrough_checks)*
+                #(#bool_checks)*
+                true
+            }
+        }
+
+        impl #impl_generics ::core::cmp::PartialEq<#struct_name #ty_generics> for #original_struct_name #ty_generics #where_clause {
+            fn eq(&self, #other: &#struct_name #ty_generics) -> bool {
+                #other == self
+            }
+        }
+    )
+}
@@ -0,0 +1,153 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ext::IdentExt, ItemStruct};
+
+use crate::{
+    args::Args,
+    impl_get_set::{generate_getter_body, generate_setter_body},
+    r#impl::{extract_cfgs, local_ident, BoolField, FlagGroup},
+};
+
+/// Generates the `FIELD_COUNT` expression and the `field_names` method, skipping fields which are configured out.
+fn generate_field_names(bool_fields: &[BoolField]) -> (TokenStream, TokenStream) {
+    let [count, names, index] = ["count", "names", "index"].map(local_ident);
+    let (counts, name_setters): (Vec<_>, Vec<_>) = bool_fields
+        .iter()
+        .map(|field| {
+            let cfgs = extract_cfgs(&field.attrs);
+            let name = field.field_ident.unraw().to_string();
+            (
+                quote!(#(#cfgs)* { #count += 1; }),
+                quote!(#(#cfgs)* { #names[#index] = #name; #index += 1; }),
+            )
+        })
+        .unzip();
+
+    let field_count = quote!({
+        let mut #count = 0;
+        #(#counts)*
+        #count
+    });
+
+    let field_names = quote!(
+        fn field_names() -> &'static [&'static str] {
+            const FIELD_COUNT: usize = #field_count;
+            const FIELD_NAMES: [&str; FIELD_COUNT] = {
+                let mut #names = [""; FIELD_COUNT];
+                let mut #index = 0;
+                #(#name_setters)*
+                #names
+            };
+
+            &FIELD_NAMES
+        }
+    );
+
+    (field_count, field_names)
+}
+
+/// Implements `BoolPacked` from the `runtime` crate path, indexing the packed fields in declaration order.
+///
+/// `BoolPacked::Flags` is the flags type of the ungrouped fields, or of the first group if every field has one.
+pub fn impl_runtime(
+    struct_item: &ItemStruct,
+    original_struct: &ItemStruct,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+    args: &Args,
+) -> Option<TokenStream> {
+    let runtime = &args.runtime.as_ref()?.0;
+    let original_name = &original_struct.ident;
+    let struct_name = &struct_item.ident;
+    let (impl_generics, ty_generics, where_clause) = struct_item.generics.split_for_impl();
+
+    let flags_group = groups
+        .iter()
+        .find(|g| g.name.is_none())
+        .unwrap_or(&groups[0]);
+    let flags_ty = &flags_group.flags_path;
+
+    let self_ = quote!(self);
+    let [index, field_index, value, original] =
+        ["index", "field_index", "value", "original"].map(local_ident);
+
+    let mut getters = Vec::with_capacity(bool_fields.len());
+    let mut setters = Vec::with_capacity(bool_fields.len());
+    for field in bool_fields {
+        let cfgs = extract_cfgs(&field.attrs);
+        let group = field.group(groups);
+        let flags_name = &group.flags_path;
+
+        let variant = match field {
+            BoolField::Normal(_) => quote!(Bool),
+            BoolField::Opt { .. } => quote!(OptBool),
+        };
+
+        let getter_body = generate_getter_body(field, &group.flag_field.read(&self_), flags_name);
+        let setter_body = group.flag_field.update(&self_, false, |flags| {
+            generate_setter_body(field, flags, flags_name, &quote!(#value))
+        });
+
+        getters.push(quote!(
+            #(#cfgs)* {
+                if #index == #field_index {
+                    let #value = #runtime::PackedValue::#variant(#getter_body);
+                    return ::core::option::Option::Some(#value);
+                }
+
+                #field_index += 1;
+            }
+        ));
+        setters.push(quote!(
+            #(#cfgs)* {
+                if #index == #field_index {
+                    let #runtime::PackedValue::#variant(#value) = #value else {
+                        return ::core::result::Result::Err(#runtime::SetByIndexError::WrongType);
+                    };
+
+                    #setter_body
+                    return ::core::result::Result::Ok(());
+                }
+
+                #field_index += 1;
+            }
+        ));
+    }
+
+    let (field_count, field_names) = generate_field_names(bool_fields);
+    Some(quote!(
+        #[allow(deprecated, unused_assignments)]
+        impl #impl_generics #runtime::BoolPacked for #struct_name #ty_generics #where_clause {
+            type Flags = #flags_ty;
+            type Original = #original_name #ty_generics;
+
+            const FIELD_COUNT: usize = #field_count;
+
+            #field_names
+
+            fn get_by_index(&self, #index: usize) -> ::core::option::Option<#runtime::PackedValue> {
+                let mut #field_index = 0;
+                #(#getters)*
+                ::core::option::Option::None
+            }
+
+            fn set_by_index(
+                &mut self,
+                #index: usize,
+                #value: #runtime::PackedValue,
+            ) -> ::core::result::Result<(), #runtime::SetByIndexError> {
+                let mut #field_index = 0;
+                #(#setters)*
+                ::core::result::Result::Err(#runtime::SetByIndexError::OutOfBounds)
+            }
+
+            fn into_original(self) -> Self::Original {
+                <Self::Original as ::core::convert::From<Self>>::from(self)
+            }
+
+            fn from_original(#original: Self::Original) -> Self {
+                <Self as ::core::convert::From<Self::Original>>::from(#original)
+            }
+        }
+    ))
+}
@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::ItemStruct;
+
+use crate::{args::Args, error::Error, r#impl::generate_allow_unused};
+
+pub fn impl_bytes_saved(
+    struct_item: &ItemStruct,
+    original_struct_name: &Ident,
+    args: &Args,
+) -> TokenStream {
+    let struct_name = &struct_item.ident;
+    let struct_vis = &struct_item.vis;
+    let (impl_generics, ty_generics, where_clause) = struct_item.generics.split_for_impl();
+
+    let bytes_saved_docs = format!(
+        "The number of bytes saved by packing the bools of [`{original_struct_name}`] into [`{struct_name}`]."
+    );
+
+    let allow_unused = generate_allow_unused(args);
+    quote!(
+        #allow_unused
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #[doc = #bytes_saved_docs]
+            #struct_vis const BYTES_SAVED: usize = ::core::mem::size_of::<#original_struct_name #ty_generics>()
+                .saturating_sub(::core::mem::size_of::<Self>());
+        }
+    )
+}
+
+fn generate_size_assertion(span: Span, condition: &TokenStream, message: &str) -> TokenStream {
+    quote_spanned!(span=> const _: () = ::core::assert!(#condition, #message);)
+}
+
+/// Emits a deprecation warning if packing saves no space, as proc macros cannot emit warnings on stable.
+fn generate_no_savings_warning(struct_name: &Ident, original_struct_name: &Ident) -> TokenStream {
+    let note = format!(
+        "bool_to_bitflags: Packing the bools of `{struct_name}` saves no space, pass `allow_no_savings` to silence this."
+    );
+
+    let check_call = quote_spanned!(struct_name.span()=> NoSavings::<{
+        ::core::mem::size_of::<#struct_name>() >= ::core::mem::size_of::<#original_struct_name>()
+    }>::check());
+
+    quote!(
+        const _: () = {
+            struct NoSavings<const NO_SAVINGS: bool>;
+            impl NoSavings<false> {
+                const fn check() {}
+            }
+
+            impl NoSavings<true> {
+                #[deprecated(note = #note)]
+                const fn check() {}
+            }
+
+            #check_call;
+        };
+    )
+}
+
+pub fn generate_size_checks(
+    struct_item: &ItemStruct,
+    original_struct_name: &Ident,
+    args: &Args,
+) -> Result<TokenStream, Error> {
+    let struct_name = &struct_item.ident;
+    if !struct_item.generics.params.is_empty() {
+        let assertion_span = match &args.assert_size {
+            Some(assert_size) => Some(assert_size.span()),
+            None => args.assert_smaller.then(|| args.assert_smaller.span()),
+        };
+
+        if let Some(span) = assertion_span {
+            return Err(Error::Custom(
+                span,
+                Cow::Borrowed(
+                    "bool_to_bitflags: Size assertions are not supported on generic structs!",
+                ),
+            ));
+        }
+
+        // Sizes of generic structs cannot be checked until they are used.
+        return Ok(TokenStream::new());
+    }
+
+    let size_of_struct = quote!(::core::mem::size_of::<#struct_name>());
+    let size_of_original = quote!(::core::mem::size_of::<#original_struct_name>());
+
+    let mut checks = TokenStream::new();
+    if let Some(expected_size) = &args.assert_size {
+        let expected = **expected_size;
+        let message = format!("bool_to_bitflags: `{struct_name}` is not {expected} bytes!");
+        checks.extend(generate_size_assertion(
+            expected_size.span(),
+            &quote!(#size_of_struct == #expected),
+            &message,
+        ));
+    }
+
+    if *args.assert_smaller {
+        let message = format!(
+            "bool_to_bitflags: `{struct_name}` is not smaller than `{original_struct_name}`!"
+        );
+        checks.extend(generate_size_assertion(
+            args.assert_smaller.span(),
+            &quote!(#size_of_struct < #size_of_original),
+            &message,
+        ));
+    }
+
+    if !args.allow_no_savings && !*args.assert_smaller {
+        checks.extend(generate_no_savings_warning(
+            struct_name,
+            original_struct_name,
+        ));
+    }
+
+    Ok(checks)
+}
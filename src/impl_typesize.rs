@@ -0,0 +1,98 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemStruct;
+
+use crate::{
+    impl_from_into::{extract_fields, extract_passthrough_fields},
+    r#impl::{local_ident, BoolField, FlagGroup},
+};
+
+#[cfg(feature = "typesize_details")]
+fn generate_size_details(
+    struct_item: &ItemStruct,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+) -> TokenStream {
+    let details = local_ident("details");
+    let fields = extract_fields(&struct_item.fields);
+    let passthrough_details = extract_passthrough_fields(fields, groups).map(|(ident, cfgs)| {
+        let name = syn::ext::IdentExt::unraw(ident).to_string();
+        quote!(
+            #(#cfgs)*
+            #details.push(::typesize::Field {
+                name: #name,
+                size: ::typesize::TypeSize::get_size(&self.#ident),
+                collection_items: ::typesize::TypeSize::get_collection_item_count(&self.#ident),
+            });
+        )
+    });
+
+    // Packed bools take up no space of their own, their size is reported by the shared flags entries.
+    let packed_details = bool_fields.iter().map(|field| {
+        let name = syn::ext::IdentExt::unraw(&field.field_ident).to_string();
+        let cfgs = crate::r#impl::extract_cfgs(&field.attrs);
+        quote!(
+            #(#cfgs)*
+            #details.push(::typesize::Field {
+                name: #name,
+                size: 0,
+                collection_items: None,
+            });
+        )
+    });
+
+    let flags_details = groups.iter().map(|group| {
+        let flag_field = &group.flag_field;
+        quote!(
+            #details.push(::typesize::Field {
+                name: ::core::stringify!(#flag_field),
+                size: ::core::mem::size_of_val(&self.#flag_field),
+                collection_items: None,
+            });
+        )
+    });
+
+    quote!(
+        fn get_size_details(&self) -> ::std::vec::Vec<::typesize::Field> {
+            let mut #details = ::std::vec::Vec::new();
+            #(#passthrough_details)*
+            #(#packed_details)*
+            #(#flags_details)*
+            #details
+        }
+    )
+}
+
+#[cfg(not(feature = "typesize_details"))]
+fn generate_size_details(_: &ItemStruct, _: &[FlagGroup], _: &[BoolField]) -> TokenStream {
+    TokenStream::new()
+}
+
+pub fn impl_typesize(
+    struct_item: &ItemStruct,
+    groups: &[FlagGroup],
+    bool_fields: &[BoolField],
+) -> TokenStream {
+    let struct_name = &struct_item.ident;
+    let (impl_generics, ty_generics, where_clause) = struct_item.generics.split_for_impl();
+
+    let extra_size = local_ident("extra_size");
+    let fields = extract_fields(&struct_item.fields);
+    let extra_sizes = extract_passthrough_fields(fields, groups).map(|(ident, cfgs)| {
+        quote!(#(#cfgs)* #extra_size += ::typesize::TypeSize::extra_size(&self.#ident);)
+    });
+
+    let size_details = generate_size_details(struct_item, groups, bool_fields);
+    quote!(
+        #[allow(deprecated)]
+        impl #impl_generics ::typesize::TypeSize for #struct_name #ty_generics #where_clause {
+            fn extra_size(&self) -> usize {
+                let mut #extra_size = 0;
+                #(#extra_sizes)*
+                #extra_size
+            }
+
+            #size_details
+        }
+    )
+}
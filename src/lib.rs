@@ -6,6 +6,10 @@
 //!
 //! This field is responsible for storing the packed bits, and should not be messed with manually, other than to initialize
 //! as all-false with `{StructName}GeneratedFlags::empty()` or all-true with `{StructName}GeneratedFlags::all()`.
+//! Fields marked with `#[bitflags(inverted)]` are the opposite, with `Option<bool>` fields only inverting the value.
+//!
+//! The flags type is generated with `bitflags!`, so `bitflags` must be nameable from the module of the struct, either
+//! as a dependency or imported, but not from imports inside of a function body around the struct.
 //!
 //! ## Arguments
 //! | Argument Name      | Type     | Default Value      | Description                                                                  |
@@ -16,6 +20,45 @@
 //! | `private_setters`  | `bool`   | Field Visibility   | If true, setters are forced to be crate-private                              |
 //! | `document_setters` | `bool`   | `false`            | If true, field documentation is used for setters, instead of getters         |
 //! | `owning_setters`   | `bool`   | `false`            | If true, setters take `self` and return `self` instead of taking `&mut self` |
+//! | `flags_derive`     | `List`   | Std derives        | The derives to put on the generated flags type                               |
+//! | `cell`             | `bool`   | `false`            | If true, flags are stored in a `Cell`, so setters only take `&self`          |
+//! | `assert_size`      | `usize`  |                    | Fails to compile if the packed struct is not this many bytes                 |
+//! | `assert_smaller`   | `bool`   | `false`            | Fails to compile if packing does not make the struct smaller                 |
+//! | `allow_no_savings` | `bool`   | `false`            | If true, the warning when packing does not save any space is silenced        |
+//! | `flags_position`   | `String` | `"last"`           | Where the flags field goes, one of `"first"`, `"last"`, or `"first_bool"`    |
+//! | `storage`          | `Path`   |                    | An existing bitflags type to store the flags in, instead of generating one   |
+//! | `getter_attrs`     | `List`   |                    | Attributes to put on every generated getter, such as `track_caller`          |
+//! | `setter_attrs`     | `List`   |                    | Attributes to put on every generated setter, such as `track_caller`          |
+//! | `allow_unused`     | `bool`   | `false`            | If true, `dead_code` warnings are silenced on the generated items            |
+//! | `debug`            | `bool`   | `false`            | If true, the expansion is written to `OUT_DIR/bool_to_bitflags`              |
+//! | `doc_hidden`       | `bool`   | `false`            | If true, the original struct and flags types are `#[doc(hidden)]`            |
+//! | `compare_original` | `bool`   | `false`            | If true, the struct can be compared with its original form                   |
+//! | `runtime`          | `Path`   |                    | Implements `BoolPacked` from `bool_to_bitflags_runtime`, at this path if set |
+//!
+//! Doc comments on packed fields go to their getters, or setters with `document_setters`, while `deprecated`,
+//! `doc(...)`, and lint level attributes go to every accessor. Other attributes stay on the original struct.
+//!
+//! ## Field Arguments
+//! Packed fields can be configured with `#[bitflags(...)]`, which cannot be applied via `cfg_attr`.
+//!
+//! | Argument Name | Type     | Default Value    | Description                                                          |
+//! |---------------|----------|------------------|----------------------------------------------------------------------|
+//! | `default`     | `Expr`   |                  | The value used by the generated `Default` impl, such as `true`       |
+//! | `inverted`    | `bool`   | `false`          | If true, the bit is stored negated, so `empty()` reads as `true`     |
+//! | `flag`        | `String` | Field name       | The name of the flag constant, uppercased by default                 |
+//! | `tag_flag`    | `String` | `{flag}_OPT_TAG` | The name of the flag constant storing if an `Option<bool>` is `Some` |
+//! | `group`       | `String` |                  | The flag group to pack the field into, separate from other groups    |
+//!
+//! Each group is packed into its own `__generated_{group}_flags` field, with its own accessors.
+//!
+//! ## Features
+//! - `typesize`: Implements `typesize::TypeSize` for the generated flags type, and for the compacted struct if
+//!   it derives `typesize::derive::TypeSize`.
+//! - `typesize_details`: Reports packed bools under their original field names in `TypeSize::get_size_details`,
+//!   with the packed flags shown as one shared entry. Requires the `details` feature of `typesize`.
+//! - `pretty`: Pretty-prints the expansions written by the `debug` argument, using `prettyplease`.
+//! - `debug`: Includes the line of the struct in the files written by the `debug` argument, as `{StructName}.{line}.rs`.
+//!   Enables the `span-locations` feature of `proc-macro2`.
 //!
 //! ## MSRV
 //! The Minimum Supported Rust Version of this crate is 1.65.
@@ -25,11 +68,21 @@
 #![allow(clippy::similar_names)]
 
 mod args;
+mod cfg_attr;
+mod collisions;
+mod debug;
 mod derive_hijack;
 mod error;
 mod r#impl;
+mod impl_bits;
+mod impl_bools;
+mod impl_default;
 mod impl_from_into;
 mod impl_get_set;
+mod impl_partial_eq;
+mod impl_runtime;
+mod impl_size;
+mod impl_typesize;
 mod strip_spans;
 
 /// See [crate level](crate) documentation.
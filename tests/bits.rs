@@ -0,0 +1,110 @@
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Default)]
+struct Settings {
+    id: u32,
+    is_admin: bool,
+    notifications: Option<bool>,
+    dark_mode: Option<bool>,
+    compact: Option<bool>,
+    beta: Option<bool>,
+}
+
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Clone, Copy, Default)]
+struct Member {
+    #[bitflags(group = "perms")]
+    can_kick: bool,
+    #[bitflags(group = "perms")]
+    can_ban: Option<bool>,
+    is_online: bool,
+}
+
+#[test]
+fn round_trip() {
+    let mut settings = Settings::default();
+    settings.set_is_admin(true);
+    settings.set_dark_mode(Some(false));
+    settings.set_beta(Some(true));
+
+    // 5 value bits and 4 tag bits do not fit into a `u8`.
+    let bits: u16 = settings.bits();
+
+    let mut restored = Settings::default();
+    restored.try_set_bits(bits).unwrap();
+    assert!(restored.is_admin());
+    assert_eq!(restored.notifications(), None);
+    assert_eq!(restored.dark_mode(), Some(false));
+    assert_eq!(restored.beta(), Some(true));
+    assert_eq!(restored.bits(), bits);
+}
+
+#[test]
+fn invalid_bits() {
+    let mut settings = Settings::default();
+    settings.set_is_admin(true);
+
+    assert_eq!(
+        settings.try_set_bits(1 << 9),
+        Err(SettingsInvalidBits::UnknownBits)
+    );
+    assert_eq!(
+        settings.try_set_bits(1 << 1),
+        Err(SettingsInvalidBits::UntaggedValue {
+            field: "notifications"
+        })
+    );
+    assert_eq!(
+        SettingsInvalidBits::UntaggedValue {
+            field: "notifications"
+        }
+        .to_string(),
+        "the value bit of `notifications` is set without its tag bit"
+    );
+
+    // Failed sets leave the flags untouched.
+    assert_eq!(settings.bits(), 1);
+}
+
+#[test]
+fn truncate_bits() {
+    let mut settings = Settings::default();
+    settings.set_bits_truncate((1 << 9) | (1 << 1) | 1);
+
+    assert!(settings.is_admin());
+    assert_eq!(settings.notifications(), None);
+    assert_eq!(settings.bits(), 1);
+}
+
+#[test]
+fn unchecked_bits() {
+    let mut settings = Settings::default();
+    unsafe { settings.set_bits_unchecked(1 | (1 << 5)) };
+
+    assert!(settings.is_admin());
+    assert_eq!(settings.notifications(), Some(false));
+}
+
+#[test]
+fn none_clears_value_bit() {
+    let mut settings = Settings::default();
+    settings.set_notifications(Some(true));
+    settings.set_notifications(None);
+
+    assert_eq!(settings.bits(), 0);
+}
+
+#[test]
+fn group_bits() {
+    let mut member = Member::default();
+    member.set_can_ban(Some(true));
+    member.set_is_online(true);
+
+    let perms_bits: u8 = member.perms_bits();
+    let mut restored = Member::default();
+    restored.try_set_perms_bits(perms_bits).unwrap();
+
+    assert_eq!(restored.can_ban(), Some(true));
+    assert!(!restored.can_kick());
+    assert!(!restored.is_online());
+    assert_eq!(restored.bits(), 0);
+}
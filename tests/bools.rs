@@ -0,0 +1,79 @@
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Default)]
+struct Human {
+    age: u8,
+    is_cool: bool,
+    is_epic: bool,
+    is_tall: Option<bool>,
+}
+
+fn describe(human: &Human) -> &'static str {
+    match human.bools() {
+        HumanBools {
+            is_cool: true,
+            is_epic: true,
+            ..
+        } => "legendary",
+        HumanBools {
+            is_tall: Some(true),
+            ..
+        } => "tall",
+        _ => "normal",
+    }
+}
+
+#[test]
+fn pattern_matching() {
+    let mut human = Human::default();
+    assert_eq!(describe(&human), "normal");
+
+    human.set_is_tall(Some(true));
+    assert_eq!(describe(&human), "tall");
+
+    let HumanBools {
+        is_cool, is_epic, ..
+    } = human.bools();
+    assert!(!is_cool && !is_epic);
+}
+
+#[test]
+fn set_bools() {
+    let mut human = Human::default();
+    human.set_bools(HumanBools {
+        is_cool: true,
+        is_epic: true,
+        is_tall: None,
+    });
+
+    assert_eq!(human.age, 0);
+    assert_eq!(describe(&human), "legendary");
+}
+
+mod account {
+    #[bool_to_bitflags::bool_to_bitflags]
+    #[derive(Default)]
+    pub struct Account {
+        pub is_active: bool,
+        is_admin: bool,
+    }
+
+    pub fn promote(account: &mut Account) {
+        let mut bools = account.bools();
+        bools.is_admin = true;
+        account.set_bools(bools);
+    }
+
+    pub fn is_admin(account: &Account) -> bool {
+        account.bools().is_admin
+    }
+}
+
+#[test]
+fn private_fields() {
+    let mut account = account::Account::default();
+    account.set_is_active(true);
+    account::promote(&mut account);
+
+    assert!(account.is_active());
+    assert!(account::is_admin(&account));
+}
@@ -0,0 +1,81 @@
+#[bool_to_bitflags::bool_to_bitflags(cell, allow_no_savings, compare_original)]
+#[derive(
+    Clone, Default, PartialEq, Eq, PartialOrd, Ord, Debug, serde::Serialize, serde::Deserialize,
+)]
+struct Node {
+    id: u32,
+    visited: bool,
+    #[bitflags(inverted)]
+    dirty: Option<bool>,
+}
+
+// Fails to compile if `T` is `Sync`, as the impl to use becomes ambiguous.
+fn assert_not_sync<T>()
+where
+    T: ?Sized,
+{
+    trait AmbiguousIfSync<A> {
+        fn some_item() {}
+    }
+
+    impl<T: ?Sized> AmbiguousIfSync<()> for T {}
+    impl<T: ?Sized + Sync> AmbiguousIfSync<u8> for T {}
+
+    let _ = <T as AmbiguousIfSync<_>>::some_item;
+}
+
+#[test]
+fn shared_setters() {
+    let node = Node::default();
+    let shared = &node;
+
+    shared.set_visited(true);
+    shared.set_dirty(Some(false));
+    assert!(node.visited());
+    assert_eq!(node.dirty(), Some(false));
+
+    shared.set_bools(NodeBools {
+        visited: false,
+        dirty: None,
+    });
+    assert!(!node.visited());
+    assert_eq!(node.dirty(), None);
+}
+
+#[test]
+fn mut_guard() {
+    let mut node = Node::default();
+    *node.visited_mut() = true;
+    assert!(node.visited());
+}
+
+#[test]
+fn conversions() {
+    let node: Node = serde_json::from_str("{\"id\":1,\"visited\":true,\"dirty\":false}").unwrap();
+    assert_eq!(
+        node,
+        NodeGeneratedOriginal {
+            id: 1,
+            visited: true,
+            dirty: Some(false),
+        }
+    );
+
+    let serialized = serde_json::to_string(&node).unwrap();
+    assert_eq!(serialized, "{\"id\":1,\"visited\":true,\"dirty\":false}");
+}
+
+#[test]
+fn not_sync() {
+    assert_not_sync::<Node>();
+}
+
+#[test]
+fn ordering() {
+    let node = Node::default();
+    let visited = Node::default();
+    visited.set_visited(true);
+
+    assert_eq!(node.cmp(&node.clone()), std::cmp::Ordering::Equal);
+    assert_ne!(node.cmp(&visited), std::cmp::Ordering::Equal);
+}
@@ -0,0 +1,49 @@
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings, compare_original)]
+#[cfg_attr(all(), derive(Clone, Copy, Debug, PartialEq))]
+#[cfg_attr(all(), cfg_attr(all(), derive(Default, serde::Serialize)))]
+#[cfg_attr(any(), derive(Hash, serde::Deserialize))]
+struct Conditional {
+    is_cool: bool,
+    #[cfg_attr(all(), cfg(any()))]
+    is_removed: bool,
+    #[cfg_attr(any(), cfg(any()))]
+    #[bitflags(default = Some(true))]
+    is_kept: Option<bool>,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
+#[derive(Default)]
+struct ForwardedAttrs {
+    #[cfg_attr(all(), deprecated)]
+    #[cfg_attr(any(), serde(rename = "unused"))]
+    is_old: bool,
+    #[cfg_attr(all(), allow(clippy::pedantic))]
+    is_new: bool,
+}
+
+#[test]
+fn conditional_derives() {
+    let mut conditional = Conditional::default();
+    assert_eq!(conditional.is_kept(), Some(true));
+
+    conditional.set_is_cool(true);
+    assert_eq!(
+        conditional,
+        ConditionalGeneratedOriginal {
+            is_cool: true,
+            is_kept: Some(true),
+        }
+    );
+
+    let serialized = serde_json::to_string(&conditional).unwrap();
+    assert_eq!(serialized, "{\"is_cool\":true,\"is_kept\":true}");
+}
+
+#[test]
+#[allow(deprecated)]
+fn conditional_field_attrs() {
+    let mut forwarded = ForwardedAttrs::default();
+    forwarded.set_is_old(true);
+    forwarded.set_is_new(true);
+    assert!(forwarded.is_old() && forwarded.is_new());
+}
@@ -0,0 +1,26 @@
+#[bool_to_bitflags::bool_to_bitflags(debug, allow_no_savings)]
+struct Debugged {
+    is_cool: bool,
+}
+
+#[test]
+fn expansion_written() {
+    // `OUT_DIR` is set for tests in `.cargo/config.toml`, and the line is only known with the `debug` feature
+    // on Rust 1.88 and above.
+    let dir = format!("{}/bool_to_bitflags", env!("OUT_DIR"));
+    let read_expansion = |file_name| std::fs::read_to_string(format!("{dir}/{file_name}"));
+    #[cfg(feature = "debug")]
+    let expansion = read_expansion("Debugged.2.rs").or_else(|_| read_expansion("Debugged.rs"));
+    #[cfg(not(feature = "debug"))]
+    let expansion = read_expansion("Debugged.rs");
+    let expansion = expansion.unwrap();
+
+    // Without the `pretty` feature, the expansion is not formatted.
+    let expansion: String = expansion.split_whitespace().collect();
+    assert!(expansion.contains("structDebuggedGeneratedOriginal"));
+    assert!(expansion.contains("fnset_is_cool(&mutself,__value:bool)"));
+
+    let mut debugged = Debugged::from(DebuggedGeneratedOriginal { is_cool: false });
+    debugged.set_is_cool(true);
+    assert!(debugged.is_cool());
+}
@@ -0,0 +1,51 @@
+fn yes() -> bool {
+    true
+}
+
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Default)]
+struct Config<T> {
+    name: T,
+    #[bitflags(default = true)]
+    enabled: bool,
+    verbose: bool,
+    #[bitflags(default = Some(false))]
+    colour: Option<bool>,
+    tristate: Option<bool>,
+}
+
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Default, serde::Deserialize)]
+struct SerdeConfig {
+    #[serde(default = "yes")]
+    from_serde: bool,
+    #[serde(default)]
+    other: bool,
+}
+
+#[test]
+fn default() {
+    let config = Config::<String>::default();
+    assert_eq!(config.name, "");
+    assert!(config.enabled());
+    assert!(!config.verbose());
+    assert_eq!(config.colour(), Some(false));
+    assert_eq!(config.tristate(), None);
+}
+
+#[test]
+fn original_default() {
+    let original = ConfigGeneratedOriginal::<u8>::default();
+    assert!(original.enabled);
+    assert_eq!(original.colour, Some(false));
+}
+
+#[test]
+fn serde_default() {
+    let config = SerdeConfig::default();
+    assert!(config.from_serde());
+    assert!(!config.other());
+
+    let config: SerdeConfig = serde_json::from_str("{}").unwrap();
+    assert!(config.from_serde());
+}
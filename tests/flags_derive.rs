@@ -0,0 +1,54 @@
+fn assert_copy<T: Copy>() {}
+fn assert_hash<T: std::hash::Hash>() {}
+
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
+#[derive(Clone, Debug, std::hash::Hash, serde::Serialize, serde::Deserialize)]
+struct Allowlisted {
+    is_cool: bool,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(flags_derive(Clone, Copy, Default), allow_no_savings)]
+#[derive(Default)]
+struct Explicit {
+    is_cool: bool,
+}
+
+mod renamed {
+    // Derives other than the standard library ones are resolved as written, from the user's module.
+    use std::fmt::Debug as Printable;
+
+    #[bool_to_bitflags::bool_to_bitflags(flags_derive(Clone, Copy, Printable), allow_no_savings)]
+    pub struct Renamed {
+        pub is_cool: bool,
+    }
+}
+
+#[test]
+fn allowlisted() {
+    assert_hash::<AllowlistedGeneratedFlags>();
+    assert_eq!(
+        format!("{:?}", AllowlistedGeneratedFlags::IS_COOL),
+        "AllowlistedGeneratedFlags(IS_COOL)"
+    );
+
+    let example: Allowlisted = serde_json::from_str("{\"is_cool\":true}").unwrap();
+    assert!(example.is_cool());
+}
+
+#[test]
+fn explicit() {
+    assert_copy::<ExplicitGeneratedFlags>();
+    assert!(!Explicit::default().is_cool());
+}
+
+#[test]
+fn renamed() {
+    assert_copy::<renamed::RenamedGeneratedFlags>();
+    assert_eq!(
+        format!("{:?}", renamed::RenamedGeneratedFlags::IS_COOL),
+        "RenamedGeneratedFlags(IS_COOL)"
+    );
+
+    let renamed = renamed::Renamed::from(renamed::RenamedGeneratedOriginal { is_cool: true });
+    assert!(renamed.is_cool());
+}
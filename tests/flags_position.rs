@@ -0,0 +1,44 @@
+fn flags_offset<T, F>(value: &T, flags: &F) -> usize {
+    flags as *const F as usize - value as *const T as usize
+}
+
+#[bool_to_bitflags::bool_to_bitflags(flags_position = "first", allow_no_savings)]
+#[repr(C)]
+#[derive(Default)]
+struct First {
+    value: u32,
+    is_cool: bool,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(flags_position = "first_bool")]
+#[repr(C)]
+#[derive(Default)]
+struct FirstBool {
+    small: u8,
+    is_cool: bool,
+    value: u32,
+    is_epic: bool,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(flags_position = "last", allow_no_savings)]
+#[repr(C)]
+#[derive(Default)]
+struct Last {
+    is_cool: bool,
+    value: u32,
+}
+
+#[test]
+fn offsets() {
+    let first = First::default();
+    assert_eq!(flags_offset(&first, &first.__generated_flags), 0);
+    assert_eq!(first.value, 0);
+
+    let first_bool = FirstBool::default();
+    assert_eq!(flags_offset(&first_bool, &first_bool.__generated_flags), 1);
+    assert_eq!((first_bool.small, first_bool.value), (0, 0));
+
+    let last = Last::default();
+    assert_eq!(flags_offset(&last, &last.__generated_flags), 4);
+    assert_eq!(last.value, 0);
+}
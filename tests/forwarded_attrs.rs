@@ -0,0 +1,29 @@
+#[bool_to_bitflags::bool_to_bitflags(compare_original)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Legacy {
+    #[deprecated(note = "use `is_new` instead")]
+    pub is_old: bool,
+    #[deprecated]
+    pub old_value: u8,
+    #[doc(hidden)]
+    pub is_hidden: Option<bool>,
+    #[cfg_attr(any(), deprecated)]
+    pub is_new: bool,
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_accessors() {
+    let mut legacy = Legacy::default();
+    legacy.set_is_old(true);
+    *legacy.is_hidden_mut() = Some(false);
+    legacy.set_is_new(true);
+
+    assert!(legacy.is_old() && *legacy.is_old_ref());
+    assert_eq!(legacy.is_hidden(), Some(false));
+
+    let original: LegacyGeneratedOriginal = legacy.into();
+    assert!(original.is_old && original.is_new);
+    assert_eq!(original.old_value, 0);
+    assert_eq!(Legacy::from(original), original);
+}
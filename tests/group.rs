@@ -0,0 +1,104 @@
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct User {
+    id: u32,
+    #[bitflags(group = "perms")]
+    can_read: bool,
+    #[bitflags(group = "perms")]
+    can_write: Option<bool>,
+    #[bitflags(group = "ui_state")]
+    is_collapsed: bool,
+    is_active: bool,
+}
+
+#[test]
+fn separate_fields() {
+    let user = User::default();
+    let _: UserPermsGeneratedFlags = user.__generated_perms_flags;
+    let _: UserUiStateGeneratedFlags = user.__generated_ui_state_flags;
+    let _: UserGeneratedFlags = user.__generated_flags;
+}
+
+#[test]
+fn group_flags() {
+    let mut user = User::default();
+    user.set_can_read(true);
+    user.set_can_write(Some(false));
+    user.set_is_collapsed(true);
+
+    assert_eq!(
+        user.perms_flags(),
+        UserPermsGeneratedFlags::CAN_READ | UserPermsGeneratedFlags::CAN_WRITE_OPT_TAG
+    );
+    assert_eq!(
+        user.ui_state_flags(),
+        UserUiStateGeneratedFlags::IS_COLLAPSED
+    );
+}
+
+#[test]
+fn clear_group() {
+    let mut user = User::from(UserGeneratedOriginal {
+        id: 1,
+        ..Default::default()
+    });
+    user.set_can_read(true);
+    user.set_can_write(Some(true));
+    user.set_is_collapsed(true);
+    user.set_is_active(true);
+
+    user.clear_perms();
+    assert!(!user.can_read());
+    assert_eq!(user.can_write(), None);
+    assert!(user.is_collapsed());
+    assert!(user.is_active());
+    assert_eq!(user.id, 1);
+}
+
+#[test]
+fn round_trip() {
+    let mut user = User::default();
+    user.set_can_write(Some(true));
+    user.set_is_active(true);
+
+    let original: UserGeneratedOriginal = user.into();
+    assert!(!original.can_read && !original.is_collapsed && original.is_active);
+    assert_eq!(original.can_write, Some(true));
+
+    let mut user = User::from(original);
+    assert_eq!(user.can_write(), Some(true));
+    assert!(user.is_active());
+
+    *user.can_read_mut() = true;
+    assert!(user.can_read());
+}
+
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
+#[derive(Default)]
+struct Document {
+    tags: Vec<String>,
+    #[bitflags(group = "perms")]
+    can_read: bool,
+    #[bitflags(group = "perms", inverted)]
+    can_write: bool,
+    is_draft: bool,
+}
+
+#[test]
+fn non_copy_group() {
+    let mut document = Document::default();
+    document.set_can_read(true);
+    document.set_can_write(true);
+    document.set_is_draft(true);
+
+    assert_eq!(
+        document.perms_flags().bits(),
+        DocumentPermsGeneratedFlags::CAN_READ.bits()
+    );
+
+    document.clear_perms();
+    assert!(!document.can_read());
+    assert!(!document.can_write());
+    assert!(document.is_draft());
+    assert!(document.tags.is_empty());
+}
@@ -1,4 +1,4 @@
-#[bool_to_bitflags::bool_to_bitflags]
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
 #[derive(PartialEq, Debug)]
 struct Test {
     #[cfg(False)]
@@ -27,3 +27,19 @@ fn test() {
         }
     )
 }
+
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
+#[derive(Default)]
+struct Exclusive {
+    #[cfg(any())]
+    flag: bool,
+    #[cfg(not(any()))]
+    flag: bool,
+}
+
+#[test]
+fn mutually_exclusive() {
+    let mut exclusive = Exclusive::default();
+    exclusive.set_flag(true);
+    assert!(exclusive.flag());
+}
@@ -0,0 +1,102 @@
+//! Checks that the expansion only uses fully qualified paths, and that its locals cannot be shadowed.
+#![allow(non_upper_case_globals, dead_code)]
+
+mod no_prelude {
+    #![no_implicit_prelude]
+
+    use ::bitflags;
+    use ::core::option::Option;
+
+    // Constants named like the bindings of the expansion, and of `bitflags!`, which would be matched as patterns.
+    pub(crate) const bits: u8 = 0;
+    pub(crate) const bools: u8 = 0;
+    pub(crate) const flags: u8 = 0;
+    pub(crate) const original: u8 = 0;
+    pub(crate) const other: u8 = 0;
+    pub(crate) const value: u8 = 0;
+
+    #[::bool_to_bitflags::bool_to_bitflags(compare_original)]
+    #[derive(
+        ::core::clone::Clone,
+        ::core::marker::Copy,
+        ::core::fmt::Debug,
+        ::core::default::Default,
+        ::core::cmp::PartialEq,
+    )]
+    pub struct Hygienic {
+        pub is_cool: bool,
+        #[bitflags(default = ::core::option::Option::Some(true))]
+        pub is_opt: Option<bool>,
+        #[bitflags(group = "perms", inverted)]
+        pub can_read: bool,
+        pub value: u16,
+    }
+
+    #[::bool_to_bitflags::bool_to_bitflags(cell, owning_setters, allow_no_savings)]
+    #[derive(::core::default::Default)]
+    pub struct Celled {
+        pub is_cool: Option<bool>,
+    }
+}
+
+mod shadowed {
+    // Items shadowing the prelude, which the expansion must not refer to.
+    struct Some;
+    struct None;
+    struct Ok;
+    struct Err;
+    trait From {}
+    trait Into {}
+    trait Default {}
+    trait PartialEq {}
+    trait Drop {}
+
+    #[bool_to_bitflags::bool_to_bitflags(allow_no_savings, compare_original)]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Shadowed {
+        pub is_cool: bool,
+        pub is_opt: Option<bool>,
+    }
+}
+
+use no_prelude::{Celled, Hygienic, HygienicGeneratedOriginal};
+
+#[test]
+fn expansion() {
+    let mut hygienic = Hygienic::default();
+    assert_eq!(hygienic.is_opt(), Some(true));
+
+    hygienic.set_is_cool(true);
+    *hygienic.can_read_mut() = true;
+    hygienic.set_bools(hygienic.bools());
+    assert!(hygienic.is_cool() && *hygienic.can_read_ref());
+
+    let original: HygienicGeneratedOriginal = hygienic.into();
+    assert_eq!(hygienic, original);
+    assert_eq!(original.value, 0);
+
+    let celled = Celled::default().set_is_cool(Some(false));
+    assert_eq!(celled.is_cool(), Some(false));
+
+    let shadowed = shadowed::Shadowed::from(shadowed::ShadowedGeneratedOriginal {
+        is_cool: true,
+        is_opt: None,
+    });
+    assert!(shadowed.is_cool());
+    assert_eq!(*shadowed.is_opt_ref(), None);
+}
+
+#[test]
+fn local_struct() {
+    const flags: u8 = 0;
+    const other: u8 = 0;
+
+    #[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
+    #[derive(Clone, Copy, Debug)]
+    struct Local {
+        is_cool: bool,
+    }
+
+    let local = Local::from(LocalGeneratedOriginal { is_cool: true });
+    assert!(local.is_cool());
+}
@@ -0,0 +1,44 @@
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Clone, Debug, PartialEq)]
+struct Switches {
+    #[bitflags(inverted)]
+    enabled: bool,
+    verbose: bool,
+    #[bitflags(inverted)]
+    colour: Option<bool>,
+}
+
+#[test]
+fn empty_is_true() {
+    let mut switches = Switches {
+        __generated_flags: SwitchesGeneratedFlags::empty(),
+    };
+
+    assert!(switches.enabled());
+    assert!(!switches.verbose());
+    assert_eq!(switches.colour(), None);
+
+    switches.set_enabled(false);
+    switches.set_colour(Some(true));
+    assert!(!switches.enabled());
+    assert_eq!(switches.colour(), Some(true));
+}
+
+#[test]
+fn conversions() {
+    let original = SwitchesGeneratedOriginal {
+        enabled: true,
+        verbose: false,
+        colour: Some(false),
+    };
+
+    let switches = Switches::from(original);
+    assert_eq!(
+        switches.__generated_flags,
+        SwitchesGeneratedFlags::COLOUR | SwitchesGeneratedFlags::COLOUR_OPT_TAG
+    );
+
+    let original: SwitchesGeneratedOriginal = switches.into();
+    assert!(original.enabled);
+    assert_eq!(original.colour, Some(false));
+}
@@ -0,0 +1,47 @@
+//! Checks that the expansion is clean under strict lints.
+#![deny(missing_docs, unreachable_pub, dead_code, clippy::pedantic)]
+
+mod private {
+    /// A struct which nothing uses.
+    #[bool_to_bitflags::bool_to_bitflags(allow_unused)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub(crate) struct Unused {
+        /// If the struct is cool.
+        pub(crate) is_cool: bool,
+        pub(crate) is_epic: Option<bool>,
+        value: u8,
+    }
+}
+
+/// A public struct.
+#[bool_to_bitflags::bool_to_bitflags(document_setters, allow_no_savings, compare_original)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Public {
+    pub is_cool: bool,
+    /// If the struct can be read.
+    #[bitflags(group = "perms")]
+    pub can_read: Option<bool>,
+    /// A value.
+    pub value: u16,
+}
+
+/// A public struct with hidden generated types.
+#[bool_to_bitflags::bool_to_bitflags(doc_hidden, allow_no_savings)]
+#[derive(Clone, Copy, Default)]
+pub struct Hidden {
+    /// If the struct is hidden.
+    pub is_hidden: bool,
+}
+
+#[test]
+fn lints() {
+    let mut public = Public::default();
+    public.set_can_read(Some(true));
+
+    let original: PublicGeneratedOriginal = public.into();
+    assert_eq!(original.can_read, Some(true));
+    assert_eq!(Public::from(original), original);
+
+    let hidden = HiddenGeneratedOriginal::from(Hidden::default());
+    assert!(!hidden.is_hidden);
+}
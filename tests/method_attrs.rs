@@ -0,0 +1,35 @@
+#[bool_to_bitflags::bool_to_bitflags(
+    getter_attrs(must_use = "check the flag", inline(always)),
+    setter_attrs(track_caller),
+    allow_no_savings
+)]
+#[derive(Default)]
+struct Configured {
+    is_cool: bool,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(setter_attrs(cfg(any())), allow_no_savings)]
+#[derive(Default)]
+struct ReadOnly {
+    is_cool: bool,
+}
+
+trait FallbackSetter {
+    fn set_is_cool(&mut self, _value: bool) -> &'static str {
+        "fallback"
+    }
+}
+
+impl FallbackSetter for ReadOnly {}
+
+#[test]
+fn attrs_applied() {
+    let mut configured = Configured::default();
+    configured.set_is_cool(true);
+    assert!(configured.is_cool());
+
+    // The inherent setter is configured out, so the trait method is used.
+    let mut read_only = ReadOnly::default();
+    assert_eq!(read_only.set_is_cool(true), "fallback");
+    assert!(!read_only.is_cool());
+}
@@ -0,0 +1,33 @@
+#![no_std]
+
+#[bool_to_bitflags::bool_to_bitflags(compare_original)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct NoStd {
+    #[bitflags(group = "extra")]
+    is_grouped: bool,
+    is_cool: bool,
+    is_opt: Option<bool>,
+    value: u16,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(cell, allow_no_savings)]
+#[derive(Default)]
+struct Celled {
+    is_cool: bool,
+}
+
+#[test]
+fn no_std() {
+    let mut no_std = NoStd::default();
+    no_std.set_is_opt(Some(true));
+    *no_std.is_grouped_mut() = true;
+    assert_eq!(no_std.is_opt(), Some(true));
+
+    let original: NoStdGeneratedOriginal = no_std.into();
+    assert!(original.is_grouped);
+    assert_eq!(no_std, original);
+
+    let celled = Celled::default();
+    celled.set_is_cool(true);
+    assert!(celled.is_cool());
+}
@@ -1,9 +1,21 @@
 #[derive(Clone, serde::Serialize)]
 struct NonCopy {}
 
-#[bool_to_bitflags::bool_to_bitflags]
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
 #[derive(Clone, serde::Serialize)]
 struct Test {
     non_copy: NonCopy,
     is_epic: bool,
 }
+
+#[test]
+fn serialize() {
+    let mut test = Test::from(TestGeneratedOriginal {
+        non_copy: NonCopy {},
+        is_epic: false,
+    });
+    test.set_is_epic(true);
+
+    let json = serde_json::to_string(&test.clone()).unwrap();
+    assert!(json.contains("non_copy"));
+}
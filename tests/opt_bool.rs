@@ -18,3 +18,24 @@ fn test() {
 
     assert_eq!(std::mem::size_of::<OptionalBool>(), 1)
 }
+
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Default)]
+struct ManyOptionalBools {
+    opt_1: Option<bool>,
+    opt_2: Option<bool>,
+    opt_3: Option<bool>,
+    opt_4: Option<bool>,
+    opt_5: Option<bool>,
+}
+
+#[test]
+fn tag_bits_counted() {
+    let mut example = ManyOptionalBools::default();
+    example.set_opt_5(Some(true));
+    assert_eq!(example.opt_5(), Some(true));
+    assert_eq!(example.opt_1(), None);
+
+    // 5 value bits and 5 tag bits do not fit into a `u8`.
+    assert_eq!(std::mem::size_of::<ManyOptionalBools>(), 2);
+}
@@ -0,0 +1,25 @@
+#[bool_to_bitflags::bool_to_bitflags(compare_original)]
+#[derive(Debug, PartialEq)]
+struct Human<T> {
+    name: T,
+    is_cool: bool,
+    #[bitflags(inverted)]
+    is_epic: Option<bool>,
+}
+
+#[test]
+fn compare_with_original() {
+    let original = HumanGeneratedOriginal {
+        name: "Ferris",
+        is_cool: true,
+        is_epic: Some(false),
+    };
+
+    let mut human = Human::from(HumanGeneratedOriginal { ..original });
+    assert_eq!(human, original);
+    assert_eq!(original, human);
+
+    human.set_is_epic(None);
+    assert_ne!(human, original);
+    assert_ne!(original, human);
+}
@@ -0,0 +1,55 @@
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Token {
+    r#type: bool,
+    r#async: Option<bool>,
+    #[bitflags(group = "type")]
+    r#match: bool,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(getter_prefix = "is_", allow_no_savings)]
+#[derive(Default)]
+struct Prefixed {
+    r#type: bool,
+}
+
+#[test]
+fn accessors() {
+    let mut token = Token::default();
+    token.set_type(true);
+    token.set_async(Some(false));
+    *token.match_mut() = true;
+
+    assert!(token.r#type());
+    assert_eq!(token.r#async(), Some(false));
+    assert!(*token.match_ref());
+
+    assert_eq!(
+        token.__generated_flags,
+        TokenGeneratedFlags::TYPE | TokenGeneratedFlags::ASYNC_OPT_TAG
+    );
+    assert_eq!(token.type_flags(), TokenTypeGeneratedFlags::MATCH);
+
+    token.clear_type();
+    assert!(!token.r#match());
+
+    let mut prefixed = Prefixed::default();
+    prefixed.set_type(true);
+    assert!(prefixed.is_type());
+}
+
+#[test]
+fn round_trip() {
+    let original = TokenGeneratedOriginal {
+        r#type: true,
+        r#async: None,
+        r#match: true,
+    };
+
+    let token = Token::from(original);
+    assert!(token.bools().r#type);
+
+    let original: TokenGeneratedOriginal = token.into();
+    assert!(original.r#type && original.r#match);
+    assert_eq!(original.r#async, None);
+}
@@ -0,0 +1,39 @@
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Default)]
+struct Settings {
+    is_enabled: bool,
+    #[bitflags(inverted)]
+    is_visible: bool,
+    is_dark: Option<bool>,
+}
+
+fn checkbox(value: &mut bool) {
+    *value = !*value;
+}
+
+#[test]
+fn mut_guard() {
+    let mut settings = Settings::default();
+    checkbox(&mut settings.is_enabled_mut());
+    checkbox(&mut settings.is_visible_mut());
+    assert!(settings.is_enabled());
+    assert!(settings.is_visible());
+
+    let mut is_visible = false;
+    std::mem::swap(&mut *settings.is_visible_mut(), &mut is_visible);
+    assert!(!settings.is_visible());
+    assert!(is_visible);
+
+    settings.is_dark_mut().get_or_insert(true);
+    assert_eq!(settings.is_dark(), Some(true));
+}
+
+#[test]
+fn static_ref() {
+    let mut settings = Settings::default();
+    settings.set_is_dark(Some(false));
+
+    assert_eq!(settings.is_enabled_ref(), &false);
+    assert_eq!(settings.is_visible_ref(), &false);
+    assert_eq!(settings.is_dark_ref(), &Some(false));
+}
@@ -0,0 +1,85 @@
+use bool_to_bitflags_runtime::{BoolPacked, PackedValue, SetByIndexError};
+
+#[bool_to_bitflags::bool_to_bitflags(runtime)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Packed {
+    is_cool: bool,
+    #[cfg(any())]
+    is_removed: bool,
+    value: u8,
+    #[bitflags(group = "perms")]
+    can_read: Option<bool>,
+    #[bitflags(inverted)]
+    r#type: bool,
+}
+
+#[bool_to_bitflags::bool_to_bitflags(
+    cell,
+    owning_setters,
+    allow_no_savings,
+    runtime = ::bool_to_bitflags_runtime
+)]
+#[derive(Default)]
+struct Celled<T> {
+    is_cool: bool,
+    inner: T,
+}
+
+fn count_set<T: BoolPacked>(packed: &T) -> usize {
+    (0..T::FIELD_COUNT)
+        .filter_map(|index| packed.get_by_index(index))
+        .filter(|value| value.is_true())
+        .count()
+}
+
+#[test]
+fn fields() {
+    assert_eq!(Packed::FIELD_COUNT, 3);
+    assert_eq!(Packed::field_names(), ["is_cool", "can_read", "type"]);
+
+    let mut packed = Packed::default();
+    assert_eq!(packed.get_by_index(2), Some(PackedValue::Bool(false)));
+    assert_eq!(packed.get_by_index(3), None);
+
+    packed.set_by_index(0, PackedValue::Bool(true)).unwrap();
+    packed
+        .set_by_index(1, PackedValue::OptBool(Some(true)))
+        .unwrap();
+    assert!(packed.is_cool());
+    assert_eq!(packed.can_read(), Some(true));
+    assert_eq!(count_set(&packed), 2);
+
+    assert_eq!(
+        packed.set_by_index(1, PackedValue::Bool(true)),
+        Err(SetByIndexError::WrongType)
+    );
+    assert_eq!(
+        packed.set_by_index(3, PackedValue::Bool(true)),
+        Err(SetByIndexError::OutOfBounds)
+    );
+}
+
+#[test]
+fn conversions() {
+    let original = PackedGeneratedOriginal {
+        is_cool: true,
+        value: 1,
+        can_read: None,
+        r#type: true,
+    };
+
+    let packed = Packed::from_original(original);
+    assert_eq!(packed.into_original(), original);
+
+    let flags: <Packed as BoolPacked>::Flags = packed.__generated_flags;
+    assert_eq!(flags, PackedGeneratedFlags::IS_COOL);
+
+    let mut celled = Celled::from_original(CelledGeneratedOriginal {
+        is_cool: false,
+        inner: "hello",
+    });
+
+    celled.set_by_index(0, true.into()).unwrap();
+    assert_eq!(count_set(&celled), 1);
+    assert_eq!(celled.into_original().inner, "hello");
+}
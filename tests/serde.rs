@@ -1,4 +1,4 @@
-#[bool_to_bitflags::bool_to_bitflags]
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
 #[derive(Debug, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
 struct Test {
     #[serde(default)]
@@ -15,7 +15,7 @@ fn serde() {
     assert_eq!(serialized_example, example_json);
 }
 
-#[bool_to_bitflags::bool_to_bitflags]
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
 #[derive(serde::Deserialize)]
 #[serde(remote = "Self")]
 struct TestRemote {
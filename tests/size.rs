@@ -0,0 +1,23 @@
+#[bool_to_bitflags::bool_to_bitflags(assert_size = 2, assert_smaller)]
+#[derive(Default)]
+struct Human {
+    age: u8,
+    is_epic: bool,
+    is_cool: bool,
+    is_tall: Option<bool>,
+}
+
+#[bool_to_bitflags::bool_to_bitflags]
+struct Generic<T> {
+    value: T,
+    bool_1: bool,
+    bool_2: bool,
+}
+
+#[test]
+fn bytes_saved() {
+    assert!(!Human::default().is_cool());
+    assert_eq!(Human::BYTES_SAVED, 2);
+    assert_eq!(Generic::<u16>::BYTES_SAVED, 0);
+    assert_eq!(Generic::<u8>::BYTES_SAVED, 1);
+}
@@ -0,0 +1,40 @@
+mod ffi {
+    bitflags::bitflags! {
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+        pub struct Permissions: u16 {
+            const READ = 1 << 0;
+            const WRITE = 1 << 4;
+            const EXECUTE_BIT = 1 << 8;
+            const HIDDEN_KNOWN = 1 << 9;
+            const HIDDEN_VALUE = 1 << 10;
+        }
+    }
+}
+
+#[bool_to_bitflags::bool_to_bitflags(storage = ffi::Permissions)]
+#[derive(Default)]
+struct File {
+    read: bool,
+    write: bool,
+    #[bitflags(flag = "EXECUTE_BIT")]
+    execute: bool,
+    #[bitflags(flag = "HIDDEN_VALUE", tag_flag = "HIDDEN_KNOWN")]
+    hidden: Option<bool>,
+}
+
+#[test]
+fn existing_bits() {
+    let mut file = File::default();
+    file.set_write(true);
+    file.set_execute(true);
+    file.set_hidden(Some(false));
+
+    assert_eq!(
+        file.__generated_flags,
+        ffi::Permissions::WRITE | ffi::Permissions::EXECUTE_BIT | ffi::Permissions::HIDDEN_KNOWN
+    );
+
+    assert!(!file.read());
+    assert!(file.write());
+    assert_eq!(file.hidden(), Some(false));
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "typesize")]
+use typesize::TypeSize;
+
+#[bool_to_bitflags::bool_to_bitflags(allow_no_savings)]
+#[derive(typesize::derive::TypeSize)]
+struct Human {
+    name: String,
+    is_epic: bool,
+    is_cool: bool,
+}
+
+fn example() -> Human {
+    HumanGeneratedOriginal {
+        name: String::from("Ferris"),
+        is_epic: true,
+        is_cool: false,
+    }
+    .into()
+}
+
+#[test]
+fn extra_size() {
+    let human = example();
+    assert_eq!(human.extra_size(), human.name.capacity());
+}
+
+#[test]
+fn bytes_saved() {
+    assert_eq!(
+        Human::BYTES_SAVED,
+        std::mem::size_of::<HumanGeneratedOriginal>() - std::mem::size_of::<Human>()
+    );
+}
+
+#[test]
+#[cfg(feature = "typesize_details")]
+fn size_details() {
+    let names: Vec<_> = example()
+        .get_size_details()
+        .into_iter()
+        .map(|field| (field.name, field.size))
+        .collect();
+
+    assert_eq!(
+        names,
+        [
+            ("name", example().name.get_size()),
+            ("is_epic", 0),
+            ("is_cool", 0),
+            ("__generated_flags", 1),
+        ]
+    );
+}
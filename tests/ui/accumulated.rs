@@ -0,0 +1,13 @@
+// Every error is reported at once, instead of stopping at the first.
+#[bool_to_bitflags::bool_to_bitflags(unknown_arg)]
+#[derive(Serialize)]
+struct Accumulated {
+    #[bitflags(unknown_field_arg)]
+    is_cool: bool,
+    #[bitflags(group = "perms")]
+    value: u8,
+    #[bitflags(default = true)]
+    is_epic: bool,
+}
+
+fn main() {}
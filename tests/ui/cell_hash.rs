@@ -0,0 +1,9 @@
+#[bool_to_bitflags::bool_to_bitflags(cell)]
+#[derive(Clone, Hash)]
+struct Node {
+    id: u8,
+    visited: bool,
+    dirty: bool,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#[bool_to_bitflags::bool_to_bitflags]
+struct Settings {
+    #[cfg_attr(feature = "inverted", bitflags(inverted))]
+    is_enabled: bool,
+    is_visible: bool,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[bool_to_bitflags::bool_to_bitflags]
+struct Collisions {
+    is_cool: bool,
+    __generated_flags: u8,
+    #[bitflags(flag = "IS_COOL")]
+    is_epic: bool,
+    #[bitflags(group = "perms")]
+    can_read: bool,
+    clear_perms: bool,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+mod ffi {
+    bitflags::bitflags! {
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+        pub struct Permissions: u8 {
+            const READ = 1 << 0;
+        }
+    }
+}
+
+#[bool_to_bitflags::bool_to_bitflags(storage = ffi::Permissions)]
+#[derive(Default)]
+struct File {
+    read: bool,
+    write: bool,
+    #[bitflags(tag_flag = "HIDDEN_KNOWN")]
+    hidden: Option<bool>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#![deny(deprecated)]
+
+#[bool_to_bitflags::bool_to_bitflags]
+struct Single {
+    is_cool: bool,
+}
+
+fn main() {}
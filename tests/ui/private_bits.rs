@@ -0,0 +1,16 @@
+mod account {
+    #[bool_to_bitflags::bool_to_bitflags]
+    #[derive(Default)]
+    pub struct Account {
+        pub is_active: bool,
+        is_admin: Option<bool>,
+    }
+}
+
+fn main() {
+    let mut account = account::Account::default();
+    let bits = account.bits();
+    let _ = account.try_set_bits(bits);
+    account.set_bits_truncate(bits);
+    let _: account::AccountInvalidBits;
+}
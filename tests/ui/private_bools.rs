@@ -0,0 +1,14 @@
+mod account {
+    #[bool_to_bitflags::bool_to_bitflags]
+    #[derive(Default)]
+    pub struct Account {
+        pub is_active: bool,
+        is_admin: bool,
+    }
+}
+
+fn main() {
+    let mut account = account::Account::default();
+    let bools = account.bools();
+    account.set_bools(bools);
+}
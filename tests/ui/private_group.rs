@@ -0,0 +1,17 @@
+mod user {
+    #[bool_to_bitflags::bool_to_bitflags]
+    #[derive(Clone, Copy, Default)]
+    pub struct User {
+        #[bitflags(group = "perms")]
+        can_read: bool,
+        #[bitflags(group = "perms")]
+        pub can_write: bool,
+        pub is_active: bool,
+    }
+}
+
+fn main() {
+    let mut user = user::User::default();
+    user.clear_perms();
+    let _ = user.perms_flags();
+}
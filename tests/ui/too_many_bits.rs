@@ -0,0 +1,71 @@
+// 64 `Option<bool>` fields use all 128 bits, so `overflow` does not fit.
+#[bool_to_bitflags::bool_to_bitflags]
+struct TooManyBits {
+    opt_0: Option<bool>,
+    opt_1: Option<bool>,
+    opt_2: Option<bool>,
+    opt_3: Option<bool>,
+    opt_4: Option<bool>,
+    opt_5: Option<bool>,
+    opt_6: Option<bool>,
+    opt_7: Option<bool>,
+    opt_8: Option<bool>,
+    opt_9: Option<bool>,
+    opt_10: Option<bool>,
+    opt_11: Option<bool>,
+    opt_12: Option<bool>,
+    opt_13: Option<bool>,
+    opt_14: Option<bool>,
+    opt_15: Option<bool>,
+    opt_16: Option<bool>,
+    opt_17: Option<bool>,
+    opt_18: Option<bool>,
+    opt_19: Option<bool>,
+    opt_20: Option<bool>,
+    opt_21: Option<bool>,
+    opt_22: Option<bool>,
+    opt_23: Option<bool>,
+    opt_24: Option<bool>,
+    opt_25: Option<bool>,
+    opt_26: Option<bool>,
+    opt_27: Option<bool>,
+    opt_28: Option<bool>,
+    opt_29: Option<bool>,
+    opt_30: Option<bool>,
+    opt_31: Option<bool>,
+    opt_32: Option<bool>,
+    opt_33: Option<bool>,
+    opt_34: Option<bool>,
+    opt_35: Option<bool>,
+    opt_36: Option<bool>,
+    opt_37: Option<bool>,
+    opt_38: Option<bool>,
+    opt_39: Option<bool>,
+    opt_40: Option<bool>,
+    opt_41: Option<bool>,
+    opt_42: Option<bool>,
+    opt_43: Option<bool>,
+    opt_44: Option<bool>,
+    opt_45: Option<bool>,
+    opt_46: Option<bool>,
+    opt_47: Option<bool>,
+    opt_48: Option<bool>,
+    opt_49: Option<bool>,
+    opt_50: Option<bool>,
+    opt_51: Option<bool>,
+    opt_52: Option<bool>,
+    opt_53: Option<bool>,
+    opt_54: Option<bool>,
+    opt_55: Option<bool>,
+    opt_56: Option<bool>,
+    opt_57: Option<bool>,
+    opt_58: Option<bool>,
+    opt_59: Option<bool>,
+    opt_60: Option<bool>,
+    opt_61: Option<bool>,
+    opt_62: Option<bool>,
+    opt_63: Option<bool>,
+    overflow: bool,
+}
+
+fn main() {}